@@ -0,0 +1,120 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Fields, Index, Member, Type};
+
+/// Implements `thinnbox::ThinDst` for a `#[repr(C)]` struct whose last field is a slice.
+///
+/// The value can then be built with `ThinBox::<Node>::build((kind, span), children)`, where the tuple holds the sized fields in declaration order.
+///
+/// The struct's fields are laid out from their types alone, so `packed` and `align` representations are rejected:
+///
+/// ```compile_fail
+/// #[derive(thinnbox::ThinDst)]
+/// #[repr(C, packed)]
+/// struct Packed {
+///     kind: u8,
+///     children: [u64],
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[derive(thinnbox::ThinDst)]
+/// #[repr(C, align(64))]
+/// struct Aligned {
+///     kind: u8,
+///     children: [u64],
+/// }
+/// ```
+///
+/// Structs without fields have no slice to end with, and are rejected too:
+///
+/// ```compile_fail
+/// #[derive(thinnbox::ThinDst)]
+/// #[repr(C)]
+/// struct Empty {}
+/// ```
+///
+/// ```compile_fail
+/// #[derive(thinnbox::ThinDst)]
+/// #[repr(C)]
+/// struct Empty();
+/// ```
+#[proc_macro_derive(ThinDst)]
+pub fn derive_thin_dst(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match thin_dst(input) {
+        Ok(x) => x.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn thin_dst(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let mut repr_c = false;
+    for attr in input.attrs.iter().filter(|x| x.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("packed") || meta.path.is_ident("align") {
+                return Err(meta.error("ThinDst doesn't support `packed` or `align` representations"));
+            }
+
+            repr_c |= meta.path.is_ident("C");
+            if meta.input.peek(syn::token::Paren) {
+                meta.input.parse::<proc_macro2::Group>()?;
+            }
+            Ok(())
+        })?;
+    }
+
+    if !repr_c {
+        return Err(Error::new(Span::call_site(), "ThinDst requires the struct to be #[repr(C)]"));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(x) => &x.fields,
+        _ => return Err(Error::new(Span::call_site(), "ThinDst can only be derived for structs")),
+    };
+
+    let members = match fields {
+        Fields::Named(x) => x.named.iter().map(|x| Member::Named(x.ident.clone().unwrap())).collect::<Vec<_>>(),
+        Fields::Unnamed(x) => (0..x.unnamed.len()).map(|i| Member::Unnamed(Index::from(i))).collect(),
+        Fields::Unit => return Err(Error::new_spanned(&input, "ThinDst requires a trailing slice field")),
+    };
+
+    let types = fields.iter().map(|x| &x.ty).collect::<Vec<_>>();
+    let Some((tail, prefix_types)) = types.split_last() else {
+        return Err(Error::new_spanned(&input, "ThinDst requires a trailing slice field"));
+    };
+    let prefix_members = &members[..prefix_types.len()];
+    let item = match tail {
+        Type::Slice(x) => &x.elem,
+        other => return Err(Error::new(other.span(), "the last field of a ThinDst must be a slice")),
+    };
+
+    let vars = (0..prefix_types.len()).map(|i| format_ident!("__field{}", i)).collect::<Vec<_>>();
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        unsafe impl #impl_generics ::thinnbox::ThinDst for #name #ty_generics #where_clause {
+            type Prefix = (#(#prefix_types,)*);
+            type Item = #item;
+
+            #[inline]
+            fn prefix_layout() -> ::core::alloc::Layout {
+                let layout = ::core::alloc::Layout::new::<()>();
+                #(
+                    let (layout, _) = layout.extend(::core::alloc::Layout::new::<#prefix_types>()).unwrap();
+                )*
+                layout
+            }
+
+            #[inline]
+            unsafe fn write_prefix(this: *mut Self, prefix: Self::Prefix) {
+                let (#(#vars,)*) = prefix;
+                #(
+                    ::core::ptr::addr_of_mut!((*this).#prefix_members).write(#vars);
+                )*
+            }
+        }
+    })
+}
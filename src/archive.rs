@@ -0,0 +1,172 @@
+#![allow(unused_imports)]
+
+use crate::{
+    raw::{try_allocate_with_header, AllocGuard},
+    ThinBox,
+};
+use docfg::docfg;
+use std::{alloc::Allocator, fmt::Debug, marker::PhantomData, ops::Deref, ptr::Pointee};
+
+#[cfg(feature = "rkyv")]
+use rkyv::{
+    boxed::{ArchivedBox, BoxResolver},
+    bytecheck::CheckBytes,
+    rancor::{Fallible, ResultExt, Source},
+    ser::{Writer, WriterExt},
+    traits::{ArchivePointee, LayoutRaw},
+    validation::{ArchiveContext, ArchiveContextExt},
+    Archive, ArchiveUnsized, Deserialize, DeserializeUnsized, Place, Portable, RawRelPtr, Serialize, SerializeUnsized,
+};
+
+/// Archived [`ThinBox`]: a single relative pointer to the archived contents' metadata, which is followed by a relative pointer to the contents.
+/// Like the box itself, it's a single (relative) pointer wide, and can be read in place without allocating.
+///
+/// The metadata and the pointer to the contents are archived as an [`ArchivedBox`], so [`as_archived_box`](Self::as_archived_box)
+/// can be used wherever an archived [`Box`] is expected.
+#[docfg(feature = "rkyv")]
+#[repr(transparent)]
+pub struct ArchivedThinBox<T: ArchivePointee + ?Sized> {
+    ptr: RawRelPtr,
+    _phtm: PhantomData<ArchivedBox<T>>,
+}
+
+#[cfg(feature = "rkyv")]
+impl<T: ArchivePointee + ?Sized> ArchivedThinBox<T> {
+    #[inline]
+    pub fn get(&self) -> &T {
+        self.as_archived_box().get()
+    }
+
+    #[inline]
+    pub fn as_archived_box(&self) -> &ArchivedBox<T> {
+        unsafe { &*self.ptr.as_ptr().cast::<ArchivedBox<T>>() }
+    }
+}
+
+/// Resolver for [`ThinBox`], with the position of its archived metadata.
+#[docfg(feature = "rkyv")]
+pub struct ThinBoxResolver {
+    pos: usize,
+}
+
+#[docfg(feature = "rkyv")]
+unsafe impl<T: ArchivePointee + ?Sized> Portable for ArchivedThinBox<T> {}
+
+#[docfg(feature = "rkyv")]
+impl<T: ?Sized + ArchiveUnsized, A: Allocator> Archive for ThinBox<T, A> {
+    type Archived = ArchivedThinBox<T::Archived>;
+    type Resolver = ThinBoxResolver;
+
+    #[inline]
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        RawRelPtr::emplace(resolver.pos, unsafe { out.cast_unchecked::<RawRelPtr>() })
+    }
+}
+
+#[docfg(feature = "rkyv")]
+impl<T, S, A> Serialize<S> for ThinBox<T, A>
+where
+    T: ?Sized + SerializeUnsized<S>,
+    S: Fallible + Writer + ?Sized,
+    A: Allocator,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        // the contents (and their dependencies) are written first, and then the header pointing to them
+        let resolver = ArchivedBox::serialize_from_ref(&**self, serializer)?;
+        serializer.align_for::<ArchivedBox<T::Archived>>()?;
+        let pos = unsafe { serializer.resolve_aligned(&Header(&**self), resolver)? };
+        return Ok(ThinBoxResolver { pos });
+    }
+}
+
+/// Archives a reference to the contents of a box as the header of an [`ArchivedThinBox`].
+#[cfg(feature = "rkyv")]
+struct Header<'a, T: ?Sized>(&'a T);
+
+#[cfg(feature = "rkyv")]
+impl<T: ?Sized + ArchiveUnsized> Archive for Header<'_, T> {
+    type Archived = ArchivedBox<T::Archived>;
+    type Resolver = BoxResolver;
+
+    #[inline]
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedBox::resolve_from_ref(self.0, resolver, out)
+    }
+}
+
+#[docfg(feature = "rkyv")]
+impl<T, D, A> Deserialize<ThinBox<T, A>, D> for ArchivedThinBox<T::Archived>
+where
+    T: ?Sized + ArchiveUnsized + LayoutRaw,
+    T::Archived: DeserializeUnsized<T, D>,
+    D: Fallible + ?Sized,
+    D::Error: Source,
+    A: Allocator + Default,
+{
+    fn deserialize(&self, deserializer: &mut D) -> Result<ThinBox<T, A>, D::Error> {
+        let alloc = A::default();
+        let metadata = self.get().deserialize_metadata();
+        let layout = T::layout_raw(metadata).into_error()?;
+
+        unsafe {
+            let meta = core::ptr::metadata(rkyv::ptr_meta::from_raw_parts_mut::<T>(core::ptr::null_mut(), metadata));
+            let ptr = try_allocate_with_header(meta, layout, |layout| alloc.allocate(layout)).into_error()?;
+
+            // the block is released if deserializing the contents fails
+            let guard = AllocGuard::<<T as Pointee>::Metadata, A>::new(ptr, layout, &alloc);
+            self.get().deserialize_unsized(deserializer, rkyv::ptr_meta::from_raw_parts_mut(ptr.as_ptr().cast(), metadata))?;
+            guard.forget();
+
+            return Ok(ThinBox::from_raw_with_alloc(ptr.cast(), alloc));
+        }
+    }
+}
+
+#[docfg(feature = "rkyv")]
+unsafe impl<T, C> CheckBytes<C> for ArchivedThinBox<T>
+where
+    T: ArchivePointee + ?Sized,
+    ArchivedBox<T>: CheckBytes<C>,
+    C: Fallible + ArchiveContext + ?Sized,
+    C::Error: Source,
+{
+    unsafe fn check_bytes(value: *const Self, context: &mut C) -> Result<(), C::Error> {
+        RawRelPtr::check_bytes(value.cast::<RawRelPtr>(), context)?;
+        let header = (*value).ptr.as_ptr_wrapping().cast::<ArchivedBox<T>>();
+        context.in_subtree(header, |context| ArchivedBox::<T>::check_bytes(header, context))
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<T: ArchivePointee + ?Sized> Deref for ArchivedThinBox<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.get()
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<T: ArchivePointee + Debug + ?Sized> Debug for ArchivedThinBox<T> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        T::fmt(self, f)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<T: ArchivePointee + PartialEq<U> + ?Sized, U: ArchivePointee + ?Sized> PartialEq<ArchivedThinBox<U>> for ArchivedThinBox<T> {
+    #[inline]
+    fn eq(&self, other: &ArchivedThinBox<U>) -> bool {
+        self.get().eq(other.get())
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<T: ArchivePointee + PartialEq<U> + ?Sized, U: ?Sized, A: Allocator> PartialEq<ThinBox<U, A>> for ArchivedThinBox<T> {
+    #[inline]
+    fn eq(&self, other: &ThinBox<U, A>) -> bool {
+        self.get().eq(&**other)
+    }
+}
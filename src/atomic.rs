@@ -0,0 +1,259 @@
+use crate::ThinBox;
+use std::{
+    alloc::{Allocator, Global},
+    fmt::Debug,
+    marker::PhantomData,
+    ptr::NonNull,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+/// Owned [`ThinBox`] that can be swapped atomically. Since a thin box is a single pointer, it's stored in an [`AtomicPtr`].
+///
+/// Only a pointer is swapped, so boxes can't carry their allocator in and out of the atomic. Instead, boxes swapped in must be allocated
+/// with a clone of the atomic's allocator (any of which can release the others' blocks), and boxes taken out get a clone of it.
+pub struct AtomicThinBox<T: ?Sized, A: Allocator = Global> {
+    ptr: AtomicPtr<()>,
+    alloc: A,
+    _phtm: PhantomData<ThinBox<T, A>>,
+}
+
+impl<T: ?Sized, A: Allocator> AtomicThinBox<T, A> {
+    #[inline]
+    pub fn new(v: ThinBox<T, A>) -> Self {
+        let (ptr, alloc) = v.into_raw_with_alloc();
+        return Self {
+            ptr: AtomicPtr::new(ptr.as_ptr()),
+            alloc,
+            _phtm: PhantomData,
+        };
+    }
+
+    /// Loads the address of the current box. It must not be dereferenced, since the box may be dropped by another thread at any moment.
+    #[inline]
+    pub fn load_raw(&self, order: Ordering) -> NonNull<()> {
+        unsafe { NonNull::new_unchecked(self.ptr.load(order)) }
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        unsafe { ThinBox::<T, A>::ref_from_raw(NonNull::new_unchecked(*self.ptr.get_mut())) }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> ThinBox<T, A> {
+        let mut this = core::mem::ManuallyDrop::new(self);
+        unsafe {
+            let ptr = NonNull::new_unchecked(*this.ptr.get_mut());
+            return ThinBox::from_raw_with_alloc(ptr, core::ptr::read(&this.alloc));
+        }
+    }
+
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        return &self.alloc;
+    }
+}
+
+impl<T: ?Sized, A: Allocator + Clone> AtomicThinBox<T, A> {
+    /// Stores `v`, returning the previous box.
+    #[inline]
+    pub fn swap(&self, v: ThinBox<T, A>, order: Ordering) -> ThinBox<T, A> {
+        let prev = self.ptr.swap(v.into_raw_with_alloc().0.as_ptr(), order);
+        return unsafe { ThinBox::from_raw_with_alloc(NonNull::new_unchecked(prev), self.alloc.clone()) };
+    }
+
+    /// Stores `v`, dropping the previous box.
+    #[inline]
+    pub fn store(&self, v: ThinBox<T, A>, order: Ordering) {
+        drop(self.swap(v, order))
+    }
+
+    /// Stores `new` if the current box is the one at `current` (as returned by [`load_raw`](Self::load_raw)), returning the previous box.
+    /// On failure, `new` is given back.
+    #[inline]
+    pub fn compare_exchange(
+        &self,
+        current: NonNull<()>,
+        new: ThinBox<T, A>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<ThinBox<T, A>, ThinBox<T, A>> {
+        let (new, alloc) = new.into_raw_with_alloc();
+        match self
+            .ptr
+            .compare_exchange(current.as_ptr(), new.as_ptr(), success, failure)
+        {
+            Ok(prev) => Ok(unsafe { ThinBox::from_raw_with_alloc(NonNull::new_unchecked(prev), alloc) }),
+            Err(_) => Err(unsafe { ThinBox::from_raw_with_alloc(new, alloc) }),
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Drop for AtomicThinBox<T, A> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            let ptr = NonNull::new_unchecked(*self.ptr.get_mut());
+            drop(ThinBox::<T, &A>::from_raw_with_alloc(ptr, &self.alloc))
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> From<ThinBox<T, A>> for AtomicThinBox<T, A> {
+    #[inline]
+    fn from(value: ThinBox<T, A>) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Debug for AtomicThinBox<T, A> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.ptr, f)
+    }
+}
+
+unsafe impl<T: ?Sized + Send, A: Allocator + Send> Send for AtomicThinBox<T, A> {}
+unsafe impl<T: ?Sized + Send, A: Allocator + Send + Sync> Sync for AtomicThinBox<T, A> {}
+
+/// Optional [`ThinBox`] that can be swapped atomically, using the null pointer for `None`.
+///
+/// As with [`AtomicThinBox`], boxes swapped in must be allocated with a clone of the atomic's allocator, and boxes taken out get a clone of it.
+pub struct AtomicOptionThinBox<T: ?Sized, A: Allocator = Global> {
+    ptr: AtomicPtr<()>,
+    alloc: A,
+    _phtm: PhantomData<ThinBox<T, A>>,
+}
+
+impl<T: ?Sized> AtomicOptionThinBox<T> {
+    #[inline]
+    pub fn new(v: Option<ThinBox<T>>) -> Self {
+        match v {
+            Some(x) => Self::from(x),
+            None => Self::new_in(Global),
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> AtomicOptionThinBox<T, A> {
+    /// Creates an empty atomic, whose boxes are released through `alloc`.
+    #[inline]
+    pub fn new_in(alloc: A) -> Self {
+        return Self {
+            ptr: AtomicPtr::new(core::ptr::null_mut()),
+            alloc,
+            _phtm: PhantomData,
+        };
+    }
+
+    /// Loads the address of the current box, if any. It must not be dereferenced, since the box may be dropped by another thread at any moment.
+    #[inline]
+    pub fn load_raw(&self, order: Ordering) -> Option<NonNull<()>> {
+        NonNull::new(self.ptr.load(order))
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        let ptr = NonNull::new(*self.ptr.get_mut())?;
+        return Some(unsafe { ThinBox::<T, A>::ref_from_raw(ptr) });
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Option<ThinBox<T, A>> {
+        let mut this = core::mem::ManuallyDrop::new(self);
+        let ptr = NonNull::new(*this.ptr.get_mut());
+        let alloc = unsafe { core::ptr::read(&this.alloc) };
+        return ptr.map(|ptr| unsafe { ThinBox::from_raw_with_alloc(ptr, alloc) });
+    }
+
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        return &self.alloc;
+    }
+}
+
+impl<T: ?Sized, A: Allocator + Clone> AtomicOptionThinBox<T, A> {
+    /// Stores `v`, returning the previous contents.
+    #[inline]
+    pub fn swap(&self, v: Option<ThinBox<T, A>>, order: Ordering) -> Option<ThinBox<T, A>> {
+        let prev = self.ptr.swap(into_ptr(v), order);
+        return NonNull::new(prev).map(|ptr| unsafe { ThinBox::from_raw_with_alloc(ptr, self.alloc.clone()) });
+    }
+
+    /// Stores `v`, dropping the previous contents.
+    #[inline]
+    pub fn store(&self, v: Option<ThinBox<T, A>>, order: Ordering) {
+        drop(self.swap(v, order))
+    }
+
+    /// Takes the current box out, leaving `None` in its place.
+    #[inline]
+    pub fn take(&self, order: Ordering) -> Option<ThinBox<T, A>> {
+        self.swap(None, order)
+    }
+
+    /// Stores `new` if the current contents are at `current` (as returned by [`load_raw`](Self::load_raw)), returning the previous contents.
+    /// On failure, `new` is given back.
+    #[inline]
+    pub fn compare_exchange(
+        &self,
+        current: Option<NonNull<()>>,
+        new: Option<ThinBox<T, A>>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> OptionExchange<T, A> {
+        let current = current.map_or(core::ptr::null_mut(), NonNull::as_ptr);
+        let new = into_ptr(new);
+        let from_raw = |ptr| unsafe { ThinBox::from_raw_with_alloc(ptr, self.alloc.clone()) };
+        match self.ptr.compare_exchange(current, new, success, failure) {
+            Ok(prev) => Ok(NonNull::new(prev).map(from_raw)),
+            Err(_) => Err(NonNull::new(new).map(from_raw)),
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Drop for AtomicOptionThinBox<T, A> {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(ptr) = NonNull::new(*self.ptr.get_mut()) {
+            unsafe { drop(ThinBox::<T, &A>::from_raw_with_alloc(ptr, &self.alloc)) }
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator + Default> Default for AtomicOptionThinBox<T, A> {
+    #[inline]
+    fn default() -> Self {
+        Self::new_in(Default::default())
+    }
+}
+
+impl<T: ?Sized, A: Allocator> From<ThinBox<T, A>> for AtomicOptionThinBox<T, A> {
+    #[inline]
+    fn from(value: ThinBox<T, A>) -> Self {
+        let (ptr, alloc) = value.into_raw_with_alloc();
+        return Self {
+            ptr: AtomicPtr::new(ptr.as_ptr()),
+            alloc,
+            _phtm: PhantomData,
+        };
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Debug for AtomicOptionThinBox<T, A> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.ptr, f)
+    }
+}
+
+unsafe impl<T: ?Sized + Send, A: Allocator + Send> Send for AtomicOptionThinBox<T, A> {}
+unsafe impl<T: ?Sized + Send, A: Allocator + Send + Sync> Sync for AtomicOptionThinBox<T, A> {}
+
+/// Result of [`AtomicOptionThinBox::compare_exchange`]: the previous contents on success, and the rejected ones on failure.
+pub type OptionExchange<T, A = Global> = Result<Option<ThinBox<T, A>>, Option<ThinBox<T, A>>>;
+
+#[inline]
+fn into_ptr<T: ?Sized, A: Allocator>(v: Option<ThinBox<T, A>>) -> *mut () {
+    v.map_or(core::ptr::null_mut(), |x| x.into_raw_with_alloc().0.as_ptr())
+}
@@ -0,0 +1,80 @@
+use crate::{ThinArc, ThinBox, ThinRc};
+use std::{
+    alloc::Allocator,
+    cmp::Ordering,
+    hash::Hash,
+    ops::{Deref, DerefMut},
+};
+
+/// Wrapper that compares, orders and hashes a thin pointer by the address of its contents instead of by value.
+#[derive(Debug, Clone, Copy, Default)]
+#[repr(transparent)]
+pub struct ByAddress<P>(pub P);
+
+impl<P> ByAddress<P> {
+    #[inline]
+    pub fn into_inner(self) -> P {
+        self.0
+    }
+}
+
+impl<P> From<P> for ByAddress<P> {
+    #[inline]
+    fn from(value: P) -> Self {
+        Self(value)
+    }
+}
+
+impl<P> Deref for ByAddress<P> {
+    type Target = P;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<P> DerefMut for ByAddress<P> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+macro_rules! impl_by_address {
+    ($($ty:ident),+) => {
+        $(
+            impl<T: ?Sized, A: Allocator> PartialEq for ByAddress<$ty<T, A>> {
+                #[inline]
+                fn eq(&self, other: &Self) -> bool {
+                    self.0.ptr == other.0.ptr
+                }
+            }
+
+            impl<T: ?Sized, A: Allocator> Eq for ByAddress<$ty<T, A>> {}
+
+            impl<T: ?Sized, A: Allocator> PartialOrd for ByAddress<$ty<T, A>> {
+                #[inline]
+                fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                    Some(self.cmp(other))
+                }
+            }
+
+            impl<T: ?Sized, A: Allocator> Ord for ByAddress<$ty<T, A>> {
+                #[inline]
+                fn cmp(&self, other: &Self) -> Ordering {
+                    self.0.ptr.cmp(&other.0.ptr)
+                }
+            }
+
+            impl<T: ?Sized, A: Allocator> Hash for ByAddress<$ty<T, A>> {
+                #[inline]
+                fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                    self.0.ptr.hash(state)
+                }
+            }
+        )+
+    };
+}
+
+impl_by_address! { ThinBox, ThinRc, ThinArc }
@@ -0,0 +1,105 @@
+use crate::{raw::AllocGuard, ThinBox};
+use std::{
+    alloc::{Allocator, Layout},
+    ffi::{CStr, OsStr},
+    ops::Deref,
+    path::Path,
+    ptr::Pointee,
+};
+
+/// Values that can clone themselves into uninitialized memory, which allows cloning unsized contents such as `[T]`, `str`
+/// or trait objects into a new thin allocation. To make a trait object clonable, add `CloneThin` as a supertrait.
+///
+/// # Safety
+/// [`clone_into_raw`](CloneThin::clone_into_raw) must fully initialize the destination with a value that has the same metadata as `self`.
+pub unsafe trait CloneThin {
+    /// Writes a clone of `self` into `dst`. If cloning panics, `dst` must be left without any initialized values.
+    ///
+    /// # Safety
+    /// `dst` must be valid for writes of [`size_of_val(self)`](core::mem::size_of_val) bytes and aligned to [`align_of_val(self)`](core::mem::align_of_val).
+    unsafe fn clone_into_raw(&self, dst: *mut u8);
+}
+
+unsafe impl<T: Clone> CloneThin for T {
+    #[inline]
+    unsafe fn clone_into_raw(&self, dst: *mut u8) {
+        dst.cast::<T>().write(self.clone())
+    }
+}
+
+unsafe impl<T: Clone> CloneThin for [T] {
+    unsafe fn clone_into_raw(&self, dst: *mut u8) {
+        struct Guard<T> {
+            dst: *mut T,
+            len: usize,
+        }
+
+        impl<T> Drop for Guard<T> {
+            #[inline]
+            fn drop(&mut self) {
+                unsafe {
+                    core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                        self.dst, self.len,
+                    ))
+                }
+            }
+        }
+
+        let mut guard = Guard {
+            dst: dst.cast::<T>(),
+            len: 0,
+        };
+
+        for x in self {
+            guard.dst.add(guard.len).write(x.clone());
+            guard.len += 1;
+        }
+
+        core::mem::forget(guard);
+    }
+}
+
+// These types own no resources, so a bitwise copy of the value (with its own size, not its byte representation) is a clone.
+macro_rules! impl_bytes {
+    ($($ty:ty),+) => {
+        $(
+            unsafe impl CloneThin for $ty {
+                #[inline]
+                unsafe fn clone_into_raw(&self, dst: *mut u8) {
+                    core::ptr::copy_nonoverlapping((self as *const Self).cast::<u8>(), dst, core::mem::size_of_val(self))
+                }
+            }
+        )+
+    };
+}
+
+impl_bytes! {
+    str,
+    CStr,
+    OsStr,
+    Path
+}
+
+impl<T: ?Sized + CloneThin, A: Allocator> ThinBox<T, A> {
+    /// Clones the contents into a new thin allocation from `alloc`.
+    pub fn clone_in<B: Allocator>(&self, alloc: B) -> ThinBox<T, B> {
+        unsafe {
+            let layout = Layout::for_value(self.deref());
+            let ptr = ThinBox::<T, B>::try_allocate_in(self.metadata(), layout, &alloc)
+                .expect("error allocating thin value");
+
+            let guard = AllocGuard::<<T as Pointee>::Metadata, B>::new(ptr, layout, &alloc);
+            T::clone_into_raw(self, ptr.as_ptr());
+            guard.forget();
+
+            return ThinBox::from_raw_with_alloc(ptr.cast(), alloc);
+        }
+    }
+}
+
+impl<T: ?Sized + CloneThin, A: Allocator + Clone> Clone for ThinBox<T, A> {
+    #[inline]
+    fn clone(&self) -> Self {
+        self.clone_in(self.alloc.clone())
+    }
+}
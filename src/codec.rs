@@ -0,0 +1,217 @@
+#![allow(unused_imports)]
+
+use crate::{slice::SliceWriter, ThinBox, ThinBoxError};
+#[cfg(any(feature = "borsh", feature = "bincode"))]
+use crate::slice::MAX_PREALLOC_BYTES;
+use docfg::docfg;
+use std::alloc::Allocator;
+
+// Both codecs treat length prefixes as untrusted, since they come from the input: at most `MAX_PREALLOC_BYTES` are allocated up front,
+// and longer values are read into a vector that only grows as the input is actually read.
+// Slices of zero-sized types are decoded like each codec's own `Vec`: borsh rejects them,
+// and bincode accepts them, counting each element as a byte against the decoder's limit.
+
+#[cfg(feature = "borsh")]
+const ZST_FORBIDDEN: &str = "slices of zero-sized types can't be decoded, since their length prefix can't be checked against the input";
+
+/// Reads a thin slice of `len` elements through `read`, where `len` was read from the input.
+#[cfg(any(feature = "borsh", feature = "bincode"))]
+fn read_slice_in<T, A: Allocator, E>(
+    len: usize,
+    alloc: A,
+    alloc_error: impl FnOnce(ThinBoxError) -> E,
+    mut read: impl FnMut() -> Result<T, E>,
+) -> Result<ThinBox<[T], A>, E> {
+    // zero-sized elements take no memory, so any length can be preallocated
+    let max_len = MAX_PREALLOC_BYTES.checked_div(core::mem::size_of::<T>()).unwrap_or(usize::MAX);
+    if len > max_len {
+        let mut v = Vec::with_capacity(max_len);
+        for _ in 0..len {
+            v.push(read()?);
+        }
+        return Ok(ThinBox::from_vec_in(v, alloc));
+    }
+
+    let mut writer = SliceWriter::try_new(len, &alloc).map_err(alloc_error)?;
+    for _ in 0..len {
+        let v = read()?;
+        unsafe { writer.push_unchecked(v) }
+    }
+
+    let ptr = writer.finish();
+    return Ok(unsafe { ThinBox::from_raw_with_alloc(ptr, alloc) });
+}
+
+/// Reads a thin byte slice of length `len` through `read`, where `len` was read from the input.
+#[cfg(any(feature = "borsh", feature = "bincode"))]
+fn read_bytes_in<A: Allocator, E>(
+    len: usize,
+    alloc: A,
+    alloc_error: impl FnOnce(ThinBoxError) -> E,
+    mut read: impl FnMut(&mut [u8]) -> Result<(), E>,
+) -> Result<ThinBox<[u8], A>, E> {
+    if len > MAX_PREALLOC_BYTES {
+        let mut v = Vec::new();
+        while v.len() < len {
+            let start = v.len();
+            v.resize(start + (len - start).min(MAX_PREALLOC_BYTES), 0);
+            read(&mut v[start..])?;
+        }
+        return Ok(ThinBox::from_vec_in(v, alloc));
+    }
+
+    let mut writer = SliceWriter::<u8, A>::try_new(len, &alloc).map_err(alloc_error)?;
+    unsafe {
+        core::ptr::write_bytes(writer.elems.as_ptr(), 0, len);
+        writer.len = len;
+        read(core::slice::from_raw_parts_mut(writer.elems.as_ptr(), len))?;
+    }
+
+    let ptr = writer.finish();
+    return Ok(unsafe { ThinBox::from_raw_with_alloc(ptr, alloc) });
+}
+
+#[docfg(feature = "borsh")]
+impl<T: ?Sized + borsh::BorshSerialize, A: Allocator> borsh::BorshSerialize for ThinBox<T, A> {
+    #[inline]
+    fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        T::serialize(self, writer)
+    }
+}
+
+#[docfg(feature = "borsh")]
+impl<T: borsh::BorshDeserialize, A: Allocator + Default> borsh::BorshDeserialize for ThinBox<T, A> {
+    #[inline]
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let v = T::deserialize_reader(reader)?;
+        return Ok(Self::new_in(v, Default::default()));
+    }
+}
+
+#[docfg(feature = "borsh")]
+impl<T: borsh::BorshDeserialize, A: Allocator + Default> borsh::BorshDeserialize for ThinBox<[T], A> {
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let len = u32::deserialize_reader(reader)? as usize;
+        if core::mem::size_of::<T>() == 0 {
+            return Err(borsh::io::Error::new(borsh::io::ErrorKind::InvalidData, ZST_FORBIDDEN));
+        }
+
+        read_slice_in(len, A::default(), borsh_error, || T::deserialize_reader(reader))
+    }
+}
+
+#[docfg(feature = "borsh")]
+impl<A: Allocator + Default> borsh::BorshDeserialize for ThinBox<str, A> {
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let len = u32::deserialize_reader(reader)? as usize;
+        let bytes = read_bytes_in(len, A::default(), borsh_error, |buf| reader.read_exact(buf))?;
+        return Self::from_utf8(bytes).map_err(|e| borsh_error(e.utf8_error()));
+    }
+}
+
+#[cfg(feature = "borsh")]
+#[inline]
+fn borsh_error<E: std::error::Error + Send + Sync + 'static>(e: E) -> borsh::io::Error {
+    borsh::io::Error::new(borsh::io::ErrorKind::InvalidData, e)
+}
+
+#[docfg(feature = "bincode")]
+impl<T: ?Sized + bincode::Encode, A: Allocator> bincode::Encode for ThinBox<T, A> {
+    #[inline]
+    fn encode<E: bincode::enc::Encoder>(&self, encoder: &mut E) -> Result<(), bincode::error::EncodeError> {
+        T::encode(self, encoder)
+    }
+}
+
+#[docfg(feature = "bincode")]
+impl<Context, T: bincode::Decode<Context>, A: Allocator + Default> bincode::Decode<Context> for ThinBox<T, A> {
+    #[inline]
+    fn decode<D: bincode::de::Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
+        let v = T::decode(decoder)?;
+        return Ok(Self::new_in(v, Default::default()));
+    }
+}
+
+#[docfg(feature = "bincode")]
+impl<'de, Context, T: bincode::BorrowDecode<'de, Context>, A: Allocator + Default> bincode::BorrowDecode<'de, Context> for ThinBox<T, A> {
+    #[inline]
+    fn borrow_decode<D: bincode::de::BorrowDecoder<'de, Context = Context>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
+        let v = T::borrow_decode(decoder)?;
+        return Ok(Self::new_in(v, Default::default()));
+    }
+}
+
+#[docfg(feature = "bincode")]
+impl<Context, T: bincode::Decode<Context>, A: Allocator + Default> bincode::Decode<Context> for ThinBox<[T], A> {
+    #[inline]
+    fn decode<D: bincode::de::Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
+        decode_slice_in(decoder, A::default(), T::decode)
+    }
+}
+
+#[docfg(feature = "bincode")]
+impl<'de, Context, T: bincode::BorrowDecode<'de, Context>, A: Allocator + Default> bincode::BorrowDecode<'de, Context> for ThinBox<[T], A> {
+    #[inline]
+    fn borrow_decode<D: bincode::de::BorrowDecoder<'de, Context = Context>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
+        decode_slice_in(decoder, A::default(), T::borrow_decode)
+    }
+}
+
+#[docfg(feature = "bincode")]
+impl<Context, A: Allocator + Default> bincode::Decode<Context> for ThinBox<str, A> {
+    #[inline]
+    fn decode<D: bincode::de::Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
+        decode_str_in(decoder, A::default())
+    }
+}
+
+#[docfg(feature = "bincode")]
+impl<'de, Context, A: Allocator + Default> bincode::BorrowDecode<'de, Context> for ThinBox<str, A> {
+    #[inline]
+    fn borrow_decode<D: bincode::de::BorrowDecoder<'de, Context = Context>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
+        decode_str_in(decoder, A::default())
+    }
+}
+
+#[cfg(feature = "bincode")]
+#[inline]
+fn decode_len<D: bincode::de::Decoder>(decoder: &mut D) -> Result<usize, bincode::error::DecodeError> {
+    let len = <u64 as bincode::Decode<D::Context>>::decode(decoder)?;
+    return usize::try_from(len).map_err(|_| bincode::error::DecodeError::OutsideUsizeRange(len));
+}
+
+#[cfg(feature = "bincode")]
+#[inline]
+fn bincode_error(e: ThinBoxError) -> bincode::error::DecodeError {
+    bincode::error::DecodeError::OtherString(e.to_string())
+}
+
+#[cfg(feature = "bincode")]
+fn decode_slice_in<T, D: bincode::de::Decoder, A: Allocator>(
+    decoder: &mut D,
+    alloc: A,
+    mut decode: impl FnMut(&mut D) -> Result<T, bincode::error::DecodeError>,
+) -> Result<ThinBox<[T], A>, bincode::error::DecodeError> {
+    let len = decode_len(decoder)?;
+    match core::mem::size_of::<T>() {
+        0 => decoder.claim_container_read::<u8>(len)?,
+        _ => decoder.claim_container_read::<T>(len)?,
+    }
+
+    read_slice_in(len, alloc, bincode_error, || {
+        // the elements claim their own bytes as they're decoded
+        decoder.unclaim_bytes_read(core::mem::size_of::<T>());
+        decode(decoder)
+    })
+}
+
+#[cfg(feature = "bincode")]
+fn decode_str_in<D: bincode::de::Decoder, A: Allocator>(decoder: &mut D, alloc: A) -> Result<ThinBox<str, A>, bincode::error::DecodeError> {
+    use bincode::de::read::Reader;
+
+    let len = decode_len(decoder)?;
+    decoder.claim_container_read::<u8>(len)?;
+
+    let bytes = read_bytes_in(len, alloc, bincode_error, |buf| decoder.reader().read(buf))?;
+    return ThinBox::from_utf8(bytes).map_err(|e| bincode::error::DecodeError::Utf8 { inner: e.utf8_error() });
+}
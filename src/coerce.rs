@@ -0,0 +1,32 @@
+use crate::ThinBox;
+use std::{
+    alloc::Allocator,
+    marker::Unsize,
+    ptr::{DynMetadata, Pointee},
+};
+
+impl<T: ?Sized, A: Allocator> ThinBox<T, A> {
+    /// Converts the box into a box of an unsized type, like `ThinBox<[T; N]>` into `ThinBox<[T]>` or `ThinBox<Foo>` into `ThinBox<dyn Trait>`.
+    /// If the new metadata fits the old header it's rewritten in place, otherwise the value is moved to a new block from the same allocator.
+    pub fn unsize<U: ?Sized>(self) -> ThinBox<U, A>
+    where
+        T: Unsize<U>,
+    {
+        unsafe {
+            let ptr: *const T = core::ptr::from_raw_parts(self.value_ptr(), self.metadata());
+            let meta = core::ptr::metadata(ptr as *const U);
+            return self.relayout(meta).expect("error allocating thin value");
+        }
+    }
+
+    /// Converts the box into a box of one of its supertraits, like `ThinBox<dyn Sub>` into `ThinBox<dyn Super>`.
+    /// Both types must be trait objects, so that the new vtable pointer always fits the old header and is rewritten in place.
+    #[inline]
+    pub fn upcast<U>(self) -> ThinBox<U, A>
+    where
+        T: Pointee<Metadata = DynMetadata<T>> + Unsize<U>,
+        U: ?Sized + Pointee<Metadata = DynMetadata<U>>,
+    {
+        self.unsize()
+    }
+}
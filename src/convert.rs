@@ -0,0 +1,105 @@
+use crate::{slice::SliceWriter, ThinBox};
+use std::{
+    alloc::{handle_alloc_error, Allocator, Layout},
+    mem::ManuallyDrop,
+    ops::Deref,
+    ptr::NonNull,
+    rc::Rc,
+    sync::Arc,
+};
+
+impl<T: ?Sized, A: Allocator> ThinBox<T, A> {
+    /// Moves the value into a [`Box`] allocated with the same allocator.
+    pub fn into_box(self) -> Box<T, A> {
+        unsafe {
+            let layout = Layout::for_value(self.deref());
+            let meta = self.metadata();
+
+            let ptr = match layout.size() {
+                0 => core::ptr::without_provenance_mut::<u8>(layout.align()),
+                _ => match self.alloc.allocate(layout) {
+                    Ok(x) => x.as_ptr().cast::<u8>(),
+                    Err(_) => handle_alloc_error(layout),
+                },
+            };
+
+            let this = ManuallyDrop::new(self);
+            core::ptr::copy_nonoverlapping(this.value_ptr(), ptr, layout.size());
+            Self::deallocate_raw(this.ptr, layout, &this.alloc);
+
+            let alloc = core::ptr::read(&this.alloc);
+            return Box::from_raw_in(core::ptr::from_raw_parts_mut(ptr, meta), alloc);
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> From<Box<T, A>> for ThinBox<T, A> {
+    /// Moves the value out of the [`Box`] by copying its `size_of_val` bytes into a thin allocation from the same allocator.
+    fn from(value: Box<T, A>) -> Self {
+        unsafe {
+            let layout = Layout::from_size_align_unchecked(
+                core::mem::size_of_val(value.deref()),
+                core::mem::align_of_val(value.deref()),
+            );
+
+            let meta = core::ptr::metadata(value.deref());
+            let ptr = Self::try_allocate_in(meta, layout, Box::allocator(&value))
+                .expect("error allocating thin value");
+
+            let (raw, alloc) = Box::into_raw_with_allocator(value);
+            core::ptr::copy_nonoverlapping(raw.cast::<u8>(), ptr.as_ptr(), layout.size());
+            if layout.size() != 0 {
+                alloc.deallocate(NonNull::new_unchecked(raw.cast()), layout);
+            }
+
+            return Self::from_raw_with_alloc(ptr.cast(), alloc);
+        }
+    }
+}
+
+impl<T, A: Allocator> From<Vec<T, A>> for ThinBox<[T], A> {
+    /// Moves the elements into a new block from the vector's allocator, and then releases the vector's buffer.
+    fn from(value: Vec<T, A>) -> Self {
+        let mut value = ManuallyDrop::new(value);
+        unsafe {
+            let alloc = core::ptr::read(value.allocator());
+            // the vector borrows the allocator back, so that its buffer is still released if allocating the box fails
+            let mut v = Vec::from_raw_parts_in(value.as_mut_ptr(), value.len(), value.capacity(), &alloc);
+
+            let mut writer = SliceWriter::new(v.len(), &alloc);
+            writer.move_from_vec(&mut v);
+            let ptr = writer.finish();
+
+            drop(v);
+            return Self::from_raw_with_alloc(ptr, alloc);
+        }
+    }
+}
+
+impl<T, A: Allocator> From<ThinBox<[T], A>> for Vec<T, A> {
+    #[inline]
+    fn from(value: ThinBox<[T], A>) -> Self {
+        value.into_box().into_vec()
+    }
+}
+
+impl From<ThinBox<str>> for String {
+    #[inline]
+    fn from(value: ThinBox<str>) -> Self {
+        String::from(value.into_box())
+    }
+}
+
+impl<T: ?Sized, A: Allocator> From<ThinBox<T, A>> for Rc<T, A> {
+    #[inline]
+    fn from(value: ThinBox<T, A>) -> Self {
+        Rc::from(value.into_box())
+    }
+}
+
+impl<T: ?Sized, A: Allocator> From<ThinBox<T, A>> for Arc<T, A> {
+    #[inline]
+    fn from(value: ThinBox<T, A>) -> Self {
+        Arc::from(value.into_box())
+    }
+}
@@ -0,0 +1,54 @@
+use crate::ThinBox;
+use std::{alloc::Allocator, any::Any, error::Error};
+
+macro_rules! impl_downcast {
+    ($($bound:path => [$($trait:path),+]),+) => {
+        $(
+            impl<A: Allocator> ThinBox<dyn $($trait+)*, A> {
+                #[inline]
+                pub fn is<T: $bound + 'static>(&self) -> bool {
+                    <dyn $($trait+)*>::is::<T>(&**self)
+                }
+
+                /// Attempts to downcast the box to a concrete type.
+                /// Since a sized value has no metadata header, the value is moved to a new block from the same allocator.
+                #[inline]
+                pub fn downcast<T: $bound + 'static>(self) -> Result<ThinBox<T, A>, Self> {
+                    match self.is::<T>() {
+                        true => unsafe { Ok(self.downcast_unchecked()) },
+                        false => Err(self),
+                    }
+                }
+
+                /// Downcasts the box to a concrete type without checking it.
+                ///
+                /// # Safety
+                /// The contained value must be of type `T`.
+                #[inline]
+                pub unsafe fn downcast_unchecked<T: $bound + 'static>(self) -> ThinBox<T, A> {
+                    debug_assert!(self.is::<T>());
+                    self.relayout(()).expect("error allocating thin value")
+                }
+
+                #[inline]
+                pub fn downcast_ref<T: $bound + 'static>(&self) -> Option<&T> {
+                    <dyn $($trait+)*>::downcast_ref::<T>(&**self)
+                }
+
+                #[inline]
+                pub fn downcast_mut<T: $bound + 'static>(&mut self) -> Option<&mut T> {
+                    <dyn $($trait+)*>::downcast_mut::<T>(&mut **self)
+                }
+            }
+        )+
+    };
+}
+
+impl_downcast! {
+    Any => [Any],
+    Any => [Any, Send],
+    Any => [Any, Send, Sync],
+    Error => [Error],
+    Error => [Error, Send],
+    Error => [Error, Send, Sync]
+}
@@ -0,0 +1,71 @@
+use crate::{
+    slice::{tail_layout, write_tail},
+    ThinBox,
+};
+use docfg::docfg;
+use std::{
+    alloc::{Allocator, Global, Layout},
+    ptr::Pointee,
+};
+
+#[docfg(feature = "derive")]
+pub use thinnbox_derive::ThinDst;
+
+/// `#[repr(C)]` structs whose last field is a slice, which can be built in place behind a [`ThinBox`] with [`ThinBox::build`].
+/// The slice's length is stored as the box's metadata.
+///
+/// This is usually implemented with `#[derive(ThinDst)]`, available with the `derive` feature.
+///
+/// # Safety
+/// [`prefix_layout`](ThinDst::prefix_layout) must be the layout of every field but the last one, as laid out by `#[repr(C)]` without `packed` or `align` (and without trailing padding),
+/// and [`write_prefix`](ThinDst::write_prefix) must initialize all of those fields.
+pub unsafe trait ThinDst: Pointee<Metadata = usize> {
+    /// The sized fields, in declaration order, as a tuple.
+    type Prefix;
+    /// The element type of the trailing slice.
+    type Item;
+
+    fn prefix_layout() -> Layout;
+
+    /// Writes the sized fields of `this`.
+    ///
+    /// # Safety
+    /// `this` must be valid for writes of the whole value.
+    unsafe fn write_prefix(this: *mut Self, prefix: Self::Prefix);
+}
+
+impl<T: ?Sized + ThinDst> ThinBox<T> {
+    #[inline]
+    pub fn build<I: IntoIterator<Item = T::Item>>(prefix: T::Prefix, iter: I) -> Self {
+        Self::build_in(prefix, iter, Global)
+    }
+}
+
+impl<T: ?Sized + ThinDst, A: Allocator> ThinBox<T, A> {
+    /// Builds the value in place from its sized fields and the elements of its trailing slice.
+    /// If the iterator doesn't report an exact size hint, it's collected into a [`Vec`] first.
+    pub fn build_in<I: IntoIterator<Item = T::Item>>(prefix: T::Prefix, iter: I, alloc: A) -> Self {
+        let iter = iter.into_iter();
+        match iter.size_hint() {
+            (min, Some(max)) if min == max => Self::build_exact_in(prefix, min, iter, alloc),
+            _ => {
+                let v = iter.collect::<Vec<_>>();
+                Self::build_exact_in(prefix, v.len(), v, alloc)
+            }
+        }
+    }
+
+    fn build_exact_in<I: IntoIterator<Item = T::Item>>(prefix: T::Prefix, len: usize, iter: I, alloc: A) -> Self {
+        unsafe {
+            let (layout, offset) = tail_layout::<T::Item>(T::prefix_layout(), len).expect("error allocating thin value");
+            let ptr = write_tail::<T::Item, A, T>(len, layout, offset, &alloc, |writer| {
+                iter.into_iter()
+                    .take(len)
+                    .for_each(|x| writer.push_unchecked(x))
+            });
+
+            T::write_prefix(core::ptr::from_raw_parts_mut(ptr.as_ptr(), len), prefix);
+            return Self::from_raw_with_alloc(ptr.cast(), alloc);
+        }
+    }
+}
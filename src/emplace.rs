@@ -0,0 +1,108 @@
+use crate::{raw::AllocGuard, ThinBox};
+use std::{
+    alloc::{Allocator, Global, Layout},
+    convert::Infallible,
+    marker::Unsize,
+    mem::MaybeUninit,
+    ptr::{NonNull, Pointee},
+};
+
+impl<T> ThinBox<T> {
+    #[inline]
+    pub fn new_with<F: FnOnce() -> T>(f: F) -> Self {
+        Self::new_with_in(f, Global)
+    }
+
+    #[inline]
+    pub fn try_new_with<E, F: FnOnce() -> Result<T, E>>(f: F) -> Result<Self, E> {
+        Self::try_new_with_in(f, Global)
+    }
+
+    /// # Safety
+    /// See [`try_init_with_in`](ThinBox::try_init_with_in).
+    #[inline]
+    pub unsafe fn try_init_with<E, F: FnOnce(&mut MaybeUninit<T>) -> Result<(), E>>(f: F) -> Result<Self, E> {
+        Self::try_init_with_in(f, Global)
+    }
+}
+
+impl<T: ?Sized> ThinBox<T> {
+    #[inline]
+    pub fn new_unsize_with<U: Unsize<T>, F: FnOnce() -> U>(f: F) -> Self {
+        Self::new_unsize_with_in(f, Global)
+    }
+}
+
+impl<T, A: Allocator> ThinBox<T, A> {
+    /// Allocates the block first and then writes the result of `f` into it.
+    ///
+    /// This lets the optimizer build the value straight in the block, but that isn't guaranteed (and usually doesn't happen in debug builds).
+    /// For values too large for the stack, use [`try_init_with_in`](Self::try_init_with_in), which hands out the slot itself.
+    #[inline]
+    pub fn new_with_in<F: FnOnce() -> T>(f: F, alloc: A) -> Self {
+        let init = |slot: &mut MaybeUninit<T>| {
+            slot.write(f());
+            Ok::<_, Infallible>(())
+        };
+
+        let res = unsafe { Self::try_emplace_by_parts_in((), init, alloc) };
+        match res {
+            Ok(x) => x,
+        }
+    }
+
+    /// Allocates the block first and then writes the result of `f` into it, with the same caveats as [`new_with_in`](Self::new_with_in).
+    /// If `f` fails (or panics), the allocation is released. Like [`new_with_in`](Self::new_with_in), this panics if allocating fails.
+    #[inline]
+    pub fn try_new_with_in<E, F: FnOnce() -> Result<T, E>>(f: F, alloc: A) -> Result<Self, E> {
+        let init = |slot: &mut MaybeUninit<T>| {
+            slot.write(f()?);
+            Ok(())
+        };
+
+        unsafe { Self::try_emplace_by_parts_in((), init, alloc) }
+    }
+
+    /// Allocates the block first and lets `f` initialize the value through a reference to its slot, so that it never has to be on the stack.
+    /// If `f` fails (or panics), the allocation is released. Like [`new_with_in`](Self::new_with_in), this panics if allocating fails.
+    ///
+    /// # Safety
+    /// If `f` returns `Ok`, it must have fully initialized the slot.
+    #[inline]
+    pub unsafe fn try_init_with_in<E, F: FnOnce(&mut MaybeUninit<T>) -> Result<(), E>>(f: F, alloc: A) -> Result<Self, E> {
+        Self::try_emplace_by_parts_in((), f, alloc)
+    }
+}
+
+impl<T: ?Sized, A: Allocator> ThinBox<T, A> {
+    /// Allocates the block first and then writes the result of `f` into it, with the same caveats as [`new_with_in`](ThinBox::new_with_in).
+    #[inline]
+    pub fn new_unsize_with_in<U: Unsize<T>, F: FnOnce() -> U>(f: F, alloc: A) -> Self {
+        let meta = core::ptr::metadata(NonNull::<U>::dangling().as_ptr() as *mut T);
+        let init = |slot: &mut MaybeUninit<U>| {
+            slot.write(f());
+            Ok::<_, Infallible>(())
+        };
+
+        let res = unsafe { Self::try_emplace_by_parts_in(meta, init, alloc) };
+        match res {
+            Ok(x) => x,
+        }
+    }
+
+    /// `init` must fully initialize the slot when it returns `Ok`.
+    unsafe fn try_emplace_by_parts_in<U, E>(
+        meta: <T as Pointee>::Metadata,
+        init: impl FnOnce(&mut MaybeUninit<U>) -> Result<(), E>,
+        alloc: A,
+    ) -> Result<Self, E> {
+        let layout = Layout::new::<U>();
+        let ptr = Self::try_allocate_in(meta, layout, &alloc).expect("error allocating thin value");
+
+        let guard = AllocGuard::<<T as Pointee>::Metadata, A>::new(ptr, layout, &alloc);
+        init(&mut *ptr.as_ptr().cast::<MaybeUninit<U>>())?;
+        guard.forget();
+
+        return Ok(Self::from_raw_with_alloc(ptr.cast(), alloc));
+    }
+}
@@ -0,0 +1,39 @@
+use std::{
+    alloc::Layout,
+    error::Error,
+    fmt::{Debug, Display},
+};
+
+/// Error returned by the fallible constructors of thin pointers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThinBoxError {
+    /// The metadata header followed by the value doesn't fit in a valid [`Layout`].
+    LayoutOverflow { metadata: Layout, value: Layout },
+    /// The requested number of elements doesn't fit in a valid [`Layout`].
+    CapacityOverflow,
+    /// The allocator failed to allocate a block with the requested layout.
+    AllocError { layout: Layout },
+}
+
+impl Display for ThinBoxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LayoutOverflow { metadata, value } => write!(
+                f,
+                "layout overflow: value of size {} and alignment {} doesn't fit after a metadata header of size {}",
+                value.size(),
+                value.align(),
+                metadata.size()
+            ),
+            Self::CapacityOverflow => f.write_str("capacity overflow"),
+            Self::AllocError { layout } => write!(
+                f,
+                "memory allocation of {} bytes with alignment {} failed",
+                layout.size(),
+                layout.align()
+            ),
+        }
+    }
+}
+
+impl Error for ThinBoxError {}
@@ -0,0 +1,141 @@
+use crate::ThinBox;
+use std::{
+    alloc::{Allocator, Layout},
+    ffi::{c_char, CStr, CString, OsStr, OsString},
+    path::{Path, PathBuf},
+};
+
+/// Moves a thin byte slice into the `U` that `f` builds over its bytes.
+///
+/// # Safety
+/// `f` must only be called with the bytes of a valid `U`, and `U` must own no resources, so that a bitwise copy of it is a valid value.
+unsafe fn cast_bytes<U: ?Sized, A: Allocator>(v: ThinBox<[u8], A>, f: unsafe fn(&[u8]) -> &U) -> ThinBox<U, A> {
+    let len = v.len();
+    let (ptr, alloc) = v.into_raw_with_alloc();
+    let bytes = core::slice::from_raw_parts(ptr.as_ptr().cast::<u8>(), len);
+
+    // SAFETY: std doesn't promise that `CStr`, `OsStr` or `Path` are laid out like `[u8]`, so nothing is assumed about it.
+    // `value` comes from the type's public constructor, and its own metadata and layout are used: the block is reused if
+    // `value` lives exactly in place of the bytes, otherwise `value` is copied into a new block.
+    let value = f(bytes);
+    let meta = core::ptr::metadata(value);
+    let layout = Layout::for_value(value);
+    if core::ptr::addr_eq(value, bytes) && layout == Layout::for_value(bytes) {
+        return ThinBox::<[u8], A>::from_raw_with_alloc(ptr, alloc)
+            .relayout(meta)
+            .expect("error allocating thin value");
+    }
+
+    let copy = ThinBox::<U, A>::try_allocate_in(meta, layout, &alloc);
+    if let Ok(copy) = copy {
+        core::ptr::copy_nonoverlapping((value as *const U).cast::<u8>(), copy.as_ptr(), layout.size());
+    }
+
+    ThinBox::<[u8], A>::deallocate_raw(ptr.cast(), Layout::for_value(bytes), &alloc);
+    let copy = copy.expect("error allocating thin value");
+    return ThinBox::from_raw_with_alloc(copy.cast(), alloc);
+}
+
+impl<A: Allocator> ThinBox<CStr, A> {
+    #[inline]
+    pub fn from_c_str_in(s: &CStr, alloc: A) -> Self {
+        unsafe {
+            cast_bytes(
+                ThinBox::copy_from_slice_in(s.to_bytes_with_nul(), alloc),
+                |x| CStr::from_bytes_with_nul_unchecked(x),
+            )
+        }
+    }
+
+    /// Copies `bytes` and appends the terminating NUL.
+    ///
+    /// # Safety
+    /// `bytes` must not contain any NUL.
+    #[cfg(feature = "serde")]
+    pub(crate) unsafe fn from_bytes_unchecked_in(bytes: &[u8], alloc: A) -> Self {
+        let mut writer = crate::slice::SliceWriter::new(bytes.len() + 1, &alloc);
+        writer.copy_from_slice(bytes);
+        writer.push_unchecked(0);
+        let ptr = writer.finish();
+        return cast_bytes(ThinBox::<[u8], A>::from_raw_with_alloc(ptr, alloc), |x| {
+            CStr::from_bytes_with_nul_unchecked(x)
+        });
+    }
+
+    /// Returns the inner pointer to this C string, which is NUL-terminated and valid for as long as the box is alive.
+    #[inline]
+    pub fn as_ptr(&self) -> *const c_char {
+        CStr::as_ptr(self)
+    }
+
+    #[inline]
+    pub fn into_c_string(self) -> CString {
+        CString::from(&*self)
+    }
+}
+
+impl<A: Allocator> ThinBox<OsStr, A> {
+    #[inline]
+    pub fn from_os_str_in(s: &OsStr, alloc: A) -> Self {
+        unsafe {
+            cast_bytes(ThinBox::copy_from_slice_in(s.as_encoded_bytes(), alloc), |x| {
+                OsStr::from_encoded_bytes_unchecked(x)
+            })
+        }
+    }
+
+    #[inline]
+    pub fn into_os_string(self) -> OsString {
+        OsString::from(&*self)
+    }
+}
+
+impl<A: Allocator> ThinBox<Path, A> {
+    #[inline]
+    pub fn from_path_in(s: &Path, alloc: A) -> Self {
+        unsafe {
+            cast_bytes(
+                ThinBox::copy_from_slice_in(s.as_os_str().as_encoded_bytes(), alloc),
+                |x| Path::new(OsStr::from_encoded_bytes_unchecked(x)),
+            )
+        }
+    }
+
+    #[inline]
+    pub fn into_path_buf(self) -> PathBuf {
+        PathBuf::from(&*self)
+    }
+}
+
+macro_rules! impl_conversions {
+    ($($ty:ty => $owned:ty as $from:ident, $into:ident),+) => {
+        $(
+            impl<A: Allocator + Default> From<&$ty> for ThinBox<$ty, A> {
+                #[inline]
+                fn from(value: &$ty) -> Self {
+                    Self::$from(value, Default::default())
+                }
+            }
+
+            impl<A: Allocator + Default> From<$owned> for ThinBox<$ty, A> {
+                #[inline]
+                fn from(value: $owned) -> Self {
+                    Self::$from(&value, Default::default())
+                }
+            }
+
+            impl<A: Allocator> From<ThinBox<$ty, A>> for $owned {
+                #[inline]
+                fn from(value: ThinBox<$ty, A>) -> Self {
+                    value.$into()
+                }
+            }
+        )+
+    };
+}
+
+impl_conversions! {
+    CStr => CString as from_c_str_in, into_c_string,
+    OsStr => OsString as from_os_str_in, into_os_string,
+    Path => PathBuf as from_path_in, into_path_buf
+}
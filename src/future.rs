@@ -2,7 +2,10 @@ use crate::ThinBox;
 use docfg::docfg;
 use std::{alloc::Allocator, future::Future, pin::Pin};
 
-impl<T: ?Sized + Future + Unpin, A: 'static + Allocator> Future for ThinBox<T, A> {
+// These impls only cover `Unpin` contents. For `!Unpin` contents (like `async` blocks), `Pin<ThinBox<T, A>>`
+// implements all of these traits through the blanket `Pin<P>` impls of `std` and `futures`, since `ThinBox` is always `Unpin`.
+
+impl<T: ?Sized + Future + Unpin, A: Allocator> Future for ThinBox<T, A> {
     type Output = T::Output;
 
     #[inline]
@@ -15,7 +18,7 @@ impl<T: ?Sized + Future + Unpin, A: 'static + Allocator> Future for ThinBox<T, A
 }
 
 #[docfg(feature = "futures")]
-impl<T: ?Sized + futures::Stream + Unpin, A: 'static + Allocator> futures::Stream
+impl<T: ?Sized + futures::Stream + Unpin, A: Allocator> futures::Stream
     for ThinBox<T, A>
 {
     type Item = T::Item;
@@ -35,7 +38,7 @@ impl<T: ?Sized + futures::Stream + Unpin, A: 'static + Allocator> futures::Strea
 }
 
 #[docfg(feature = "futures")]
-impl<Item, T: ?Sized + futures::Sink<Item> + Unpin, A: 'static + Allocator> futures::Sink<Item>
+impl<Item, T: ?Sized + futures::Sink<Item> + Unpin, A: Allocator> futures::Sink<Item>
     for ThinBox<T, A>
 {
     type Error = T::Error;
@@ -71,7 +74,7 @@ impl<Item, T: ?Sized + futures::Sink<Item> + Unpin, A: 'static + Allocator> futu
 }
 
 #[docfg(feature = "futures")]
-impl<T: ?Sized + futures::AsyncRead + Unpin, A: 'static + Allocator> futures::AsyncRead
+impl<T: ?Sized + futures::AsyncRead + Unpin, A: Allocator> futures::AsyncRead
     for ThinBox<T, A>
 {
     #[inline]
@@ -85,7 +88,7 @@ impl<T: ?Sized + futures::AsyncRead + Unpin, A: 'static + Allocator> futures::As
 }
 
 #[docfg(feature = "futures")]
-impl<T: ?Sized + futures::AsyncWrite + Unpin, A: 'static + Allocator> futures::AsyncWrite
+impl<T: ?Sized + futures::AsyncWrite + Unpin, A: Allocator> futures::AsyncWrite
     for ThinBox<T, A>
 {
     #[inline]
@@ -115,7 +118,7 @@ impl<T: ?Sized + futures::AsyncWrite + Unpin, A: 'static + Allocator> futures::A
 }
 
 #[docfg(feature = "futures")]
-impl<T: ?Sized + futures::AsyncBufRead + Unpin, A: 'static + Allocator> futures::AsyncBufRead
+impl<T: ?Sized + futures::AsyncBufRead + Unpin, A: Allocator> futures::AsyncBufRead
     for ThinBox<T, A>
 {
     #[inline]
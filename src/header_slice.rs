@@ -0,0 +1,108 @@
+use crate::{
+    slice::{tail_layout, write_tail},
+    ThinBox,
+};
+use std::alloc::{Allocator, Global, Layout};
+
+/// Fixed header followed by a dynamically sized tail, such as `[T]` or `str`, in a single allocation.
+/// Inside a [`ThinBox`], the tail's length is kept in the metadata slot, so the whole value sits behind one pointer.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(C)]
+pub struct HeaderSlice<H, T: ?Sized> {
+    header: H,
+    slice: T,
+}
+
+impl<H, T: ?Sized> HeaderSlice<H, T> {
+    #[inline]
+    pub fn header(&self) -> &H {
+        &self.header
+    }
+
+    #[inline]
+    pub fn header_mut(&mut self) -> &mut H {
+        &mut self.header
+    }
+
+    #[inline]
+    pub fn slice(&self) -> &T {
+        &self.slice
+    }
+
+    #[inline]
+    pub fn slice_mut(&mut self) -> &mut T {
+        &mut self.slice
+    }
+}
+
+impl<H, T> ThinBox<HeaderSlice<H, [T]>> {
+    #[inline]
+    pub fn from_header_and_iter<I: IntoIterator<Item = T>>(header: H, iter: I) -> Self {
+        Self::from_header_and_iter_in(header, iter, Global)
+    }
+
+    #[inline]
+    pub fn from_header_and_slice(header: H, v: &[T]) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_header_and_slice_in(header, v, Global)
+    }
+}
+
+impl<H, T, A: Allocator> ThinBox<HeaderSlice<H, [T]>, A> {
+    /// Creates a new header-slice from the iterator's elements. If the iterator doesn't report an exact size hint, it's collected into a [`Vec`] first.
+    pub fn from_header_and_iter_in<I: IntoIterator<Item = T>>(header: H, iter: I, alloc: A) -> Self {
+        let iter = iter.into_iter();
+        match iter.size_hint() {
+            (min, Some(max)) if min == max => Self::from_header_and_exact_iter_in(header, min, iter, alloc),
+            _ => {
+                let v = iter.collect::<Vec<_>>();
+                Self::from_header_and_exact_iter_in(header, v.len(), v, alloc)
+            }
+        }
+    }
+
+    #[inline]
+    pub fn from_header_and_slice_in(header: H, v: &[T], alloc: A) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_header_and_exact_iter_in(header, v.len(), v.iter().cloned(), alloc)
+    }
+
+    fn from_header_and_exact_iter_in<I: IntoIterator<Item = T>>(header: H, len: usize, iter: I, alloc: A) -> Self {
+        unsafe {
+            let (layout, offset) = tail_layout::<T>(Layout::new::<H>(), len).expect("error allocating thin value");
+            let ptr = write_tail::<T, A, HeaderSlice<H, [T]>>(len, layout, offset, &alloc, |writer| {
+                iter.into_iter()
+                    .take(len)
+                    .for_each(|x| writer.push_unchecked(x))
+            });
+
+            ptr.cast::<H>().write(header);
+            return Self::from_raw_with_alloc(ptr.cast(), alloc);
+        }
+    }
+}
+
+impl<H> ThinBox<HeaderSlice<H, str>> {
+    #[inline]
+    pub fn from_header_and_str(header: H, s: &str) -> Self {
+        Self::from_header_and_str_in(header, s, Global)
+    }
+}
+
+impl<H, A: Allocator> ThinBox<HeaderSlice<H, str>, A> {
+    pub fn from_header_and_str_in(header: H, s: &str, alloc: A) -> Self {
+        unsafe {
+            let (layout, offset) = tail_layout::<u8>(Layout::new::<H>(), s.len()).expect("error allocating thin value");
+            let ptr = write_tail::<u8, A, HeaderSlice<H, str>>(s.len(), layout, offset, &alloc, |writer| {
+                writer.copy_from_slice(s.as_bytes())
+            });
+
+            ptr.cast::<H>().write(header);
+            return Self::from_raw_with_alloc(ptr.cast(), alloc);
+        }
+    }
+}
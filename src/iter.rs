@@ -1,10 +1,21 @@
 use std::{alloc::Allocator, iter::{FusedIterator}};
 use crate::ThinBox;
 
+impl<I, A: Allocator> ThinBox<I, A> {
+    /// Collects the iterator into a new `I`, and moves it into a box allocated with `alloc`.
+    #[inline]
+    pub fn from_iter_in<B, T: IntoIterator<Item = B>>(iter: T, alloc: A) -> Self
+    where
+        I: FromIterator<B>,
+    {
+        Self::new_in(I::from_iter(iter), alloc)
+    }
+}
+
 impl<B, I: FromIterator<B>, A: Allocator + Default> FromIterator<B> for ThinBox<I, A> {
     #[inline]
     fn from_iter<T: IntoIterator<Item = B>>(iter: T) -> Self {
-        Self::new_in(I::from_iter(iter), Default::default())
+        Self::from_iter_in(iter, Default::default())
     }
 }
 
@@ -53,4 +64,11 @@ impl<T: ?Sized + ExactSizeIterator, A: Allocator> ExactSizeIterator for ThinBox<
     }
 }
 
-impl<T: ?Sized + FusedIterator, A: Allocator> FusedIterator for ThinBox<T, A> {}
\ No newline at end of file
+impl<T: ?Sized + FusedIterator, A: Allocator> FusedIterator for ThinBox<T, A> {}
+
+impl<T, A: Allocator + Default> FromIterator<T> for ThinBox<[T], A> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_iter_in(iter, Default::default())
+    }
+}
@@ -26,7 +26,7 @@ macro_rules! flat_mod {
 }
 
 use std::{
-    alloc::{AllocError, Allocator, Global, Layout},
+    alloc::{Allocator, Global, Layout},
     fmt::{Debug, Display},
     marker::{PhantomData, Unsize},
     mem::ManuallyDrop,
@@ -34,7 +34,23 @@ use std::{
     ptr::{NonNull, Pointee},
 };
 
-flat_mod! { r#fn, iter, ops, future, ser_de, io }
+flat_mod! { r#fn, iter, ops, future, ser_de, io, string, error, clone, by_address, rc, sync, vec, header_slice, dst, atomic, tagged, archive }
+mod codec;
+mod coerce;
+mod convert;
+mod downcast;
+mod emplace;
+mod ffi;
+mod pin;
+mod raw;
+mod slice;
+mod uninit;
+
+#[cfg(feature = "registry")]
+#[cfg_attr(docsrs, doc(cfg(feature = "registry")))]
+mod registry;
+#[cfg(feature = "registry")]
+pub use registry::*;
 
 pub struct ThinBox<T: ?Sized, A: Allocator = Global> {
     ptr: NonNull<u8>,
@@ -49,7 +65,7 @@ impl<T> ThinBox<T> {
     }
 
     #[inline]
-    pub fn try_new(t: T) -> Result<Self, AllocError> {
+    pub fn try_new(t: T) -> Result<Self, ThinBoxError> {
         Self::try_new_in(t, Global)
     }
 }
@@ -61,7 +77,7 @@ impl<T: ?Sized> ThinBox<T> {
     }
 
     #[inline]
-    pub fn try_new_unsize<U: Unsize<T>>(v: U) -> Result<Self, AllocError> {
+    pub fn try_new_unsize<U: Unsize<T>>(v: U) -> Result<Self, ThinBoxError> {
         Self::try_new_unsize_in(v, Global)
     }
 }
@@ -73,7 +89,7 @@ impl<T, A: Allocator> ThinBox<T, A> {
     }
 
     #[inline]
-    pub fn try_new_in(t: T, alloc: A) -> Result<Self, AllocError> {
+    pub fn try_new_in(t: T, alloc: A) -> Result<Self, ThinBoxError> {
         unsafe { Self::try_new_by_parts_in((), t, alloc) }
     }
 }
@@ -85,7 +101,7 @@ impl<T: ?Sized, A: Allocator> ThinBox<T, A> {
     }
 
     #[inline]
-    pub fn try_new_unsize_in<U: Unsize<T>>(v: U, alloc: A) -> Result<Self, AllocError> {
+    pub fn try_new_unsize_in<U: Unsize<T>>(v: U, alloc: A) -> Result<Self, ThinBoxError> {
         unsafe { Self::try_new_by_parts_in(core::ptr::metadata(&v as &T), v, alloc) }
     }
 
@@ -93,34 +109,66 @@ impl<T: ?Sized, A: Allocator> ThinBox<T, A> {
         meta: <T as Pointee>::Metadata,
         v: U,
         alloc: A,
-    ) -> Result<Self, AllocError> {
-        let (layout, offset) =
-            match Layout::new::<<T as Pointee>::Metadata>().extend(Layout::new::<U>()) {
-                Ok(x) => x,
-                Err(e) => {
-                    #[cfg(debug_assertions)]
-                    eprintln!("{e}");
-                    return Err(AllocError);
-                }
-            };
-
-        let ptr = alloc.allocate(layout)?.as_ptr().cast::<u8>().add(offset);
-        debug_assert!(!ptr.is_null());
+    ) -> Result<Self, ThinBoxError> {
+        let ptr = Self::try_allocate_in(meta, Layout::new::<U>(), &alloc)?;
+        unsafe { core::ptr::write(ptr.as_ptr().cast(), v) };
+        return Ok(Self::from_raw_with_alloc(ptr.cast(), alloc));
+    }
 
-        unsafe {
-            core::ptr::write(ptr.cast(), v);
-            core::ptr::write(
-                ptr.sub(core::mem::size_of::<<T as Pointee>::Metadata>())
-                    .cast(),
-                meta,
-            );
+    /// Allocates a block for the metadata followed by a value with the given layout, and writes the metadata into its header.
+    /// The returned pointer points to the (uninitialized) value.
+    #[inline]
+    pub(crate) unsafe fn try_allocate_in(
+        meta: <T as Pointee>::Metadata,
+        layout: Layout,
+        alloc: &A,
+    ) -> Result<NonNull<u8>, ThinBoxError> {
+        raw::try_allocate_with_header(meta, layout, |layout| alloc.allocate(layout))
+    }
+
+    /// Same as [`try_allocate_in`](Self::try_allocate_in), but the value's memory is zeroed.
+    #[inline]
+    pub(crate) unsafe fn try_allocate_zeroed_in(
+        meta: <T as Pointee>::Metadata,
+        layout: Layout,
+        alloc: &A,
+    ) -> Result<NonNull<u8>, ThinBoxError> {
+        raw::try_allocate_with_header(meta, layout, |layout| alloc.allocate_zeroed(layout))
+    }
+
+    /// Reinterprets the value as a `U` with the given metadata, which must describe a value with the same layout.
+    /// If the new header fits the old block the metadata is rewritten in place, otherwise the value is moved to a new block from the same allocator.
+    pub(crate) unsafe fn relayout<U: ?Sized>(
+        self,
+        meta: <U as Pointee>::Metadata,
+    ) -> Result<ThinBox<U, A>, ThinBoxError> {
+        let value = Layout::for_value(self.deref());
+        let old = Layout::new::<<T as Pointee>::Metadata>()
+            .extend(value)
+            .unwrap_unchecked();
+
+        if Layout::new::<<U as Pointee>::Metadata>().extend(value).ok() == Some(old) {
+            let (ptr, alloc) = self.into_raw_with_alloc();
+            ptr.as_ptr()
+                .byte_sub(core::mem::size_of::<<U as Pointee>::Metadata>())
+                .cast::<<U as Pointee>::Metadata>()
+                .write(meta);
+
+            return Ok(ThinBox::from_raw_with_alloc(ptr, alloc));
         }
 
-        return Ok(Self {
-            ptr: unsafe { NonNull::new_unchecked(ptr) },
-            alloc,
-            _phtm: PhantomData,
-        });
+        let ptr = ThinBox::<U, A>::try_allocate_in(meta, value, &self.alloc)?;
+        let this = ManuallyDrop::new(self);
+        core::ptr::copy_nonoverlapping(this.value_ptr(), ptr.as_ptr(), value.size());
+        Self::deallocate_raw(this.ptr, value, &this.alloc);
+
+        return Ok(ThinBox::from_raw_with_alloc(ptr.cast(), core::ptr::read(&this.alloc)));
+    }
+
+    /// Releases a block returned by [`try_allocate_in`](Self::try_allocate_in) without dropping its value.
+    #[inline]
+    pub(crate) unsafe fn deallocate_raw(ptr: NonNull<u8>, layout: Layout, alloc: &A) {
+        raw::deallocate_with_header::<<T as Pointee>::Metadata, A>(ptr, layout, alloc)
     }
 }
 
@@ -290,4 +338,4 @@ impl<T: ?Sized, A: Allocator> Drop for ThinBox<T, A> {
 
 unsafe impl<T: ?Sized + Send, A: Allocator + Send> Send for ThinBox<T, A> {}
 unsafe impl<T: ?Sized + Sync, A: Allocator + Sync> Sync for ThinBox<T, A> {}
-impl<T: ?Sized, A: 'static + Allocator> Unpin for ThinBox<T, A> {}
+impl<T: ?Sized, A: Allocator> Unpin for ThinBox<T, A> {}
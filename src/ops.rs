@@ -1,13 +1,11 @@
-use std::{alloc::Allocator, error::Error, hash::Hash};
+use std::{
+    alloc::Allocator,
+    borrow::{Borrow, BorrowMut},
+    error::Error,
+    hash::Hash,
+};
 use crate::ThinBox;
 
-impl<T: Clone, A: Allocator + Clone> Clone for ThinBox<T, A> {
-    #[inline]
-    fn clone(&self) -> Self {
-        Self::new_in(T::clone(self), self.alloc.clone())
-    }
-}
-
 impl<T: Default, A: Allocator + Default> Default for ThinBox<T, A> {
     #[inline]
     fn default() -> Self {
@@ -53,7 +51,7 @@ impl<T: ?Sized + Ord, A: Allocator> Ord for ThinBox<T, A> {
 impl<T: ?Sized + Hash, A: Allocator> Hash for ThinBox<T, A> {
     #[inline]
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.ptr.hash(state);
+        T::hash(self, state)
     }
 }
 
@@ -64,4 +62,32 @@ impl<T: ?Sized + Error, A: Allocator> Error for ThinBox<T, A> {
     }
 }
 
-impl<T: ?Sized + Eq, A: Allocator> Eq for ThinBox<T, A> {}
\ No newline at end of file
+impl<T: ?Sized + Eq, A: Allocator> Eq for ThinBox<T, A> {}
+
+impl<T: ?Sized, A: Allocator> AsRef<T> for ThinBox<T, A> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized, A: Allocator> AsMut<T> for ThinBox<T, A> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Borrow<T> for ThinBox<T, A> {
+    #[inline]
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized, A: Allocator> BorrowMut<T> for ThinBox<T, A> {
+    #[inline]
+    fn borrow_mut(&mut self) -> &mut T {
+        self
+    }
+}
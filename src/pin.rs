@@ -0,0 +1,48 @@
+use crate::ThinBox;
+use std::{
+    alloc::{Allocator, Global},
+    marker::Unsize,
+    pin::Pin,
+};
+
+impl<T> ThinBox<T> {
+    #[inline]
+    pub fn pin(v: T) -> Pin<Self> {
+        Self::pin_in(v, Global)
+    }
+}
+
+impl<T: ?Sized> ThinBox<T> {
+    /// Pins an unsized value, like an `async` block behind a `ThinBox<dyn Future>`.
+    #[inline]
+    pub fn pin_unsize<U: Unsize<T>>(v: U) -> Pin<Self> {
+        Self::pin_unsize_in(v, Global)
+    }
+}
+
+impl<T, A: 'static + Allocator> ThinBox<T, A> {
+    #[inline]
+    pub fn pin_in(v: T, alloc: A) -> Pin<Self> {
+        Self::new_in(v, alloc).into_pin()
+    }
+}
+
+impl<T: ?Sized, A: 'static + Allocator> ThinBox<T, A> {
+    #[inline]
+    pub fn pin_unsize_in<U: Unsize<T>>(v: U, alloc: A) -> Pin<Self> {
+        Self::new_unsize_in(v, alloc).into_pin()
+    }
+
+    /// Pins the box. The allocator must be `'static`, since otherwise the memory could be reclaimed without the value being dropped.
+    #[inline]
+    pub fn into_pin(self) -> Pin<Self> {
+        unsafe { Pin::new_unchecked(self) }
+    }
+}
+
+impl<T: ?Sized, A: 'static + Allocator> From<ThinBox<T, A>> for Pin<ThinBox<T, A>> {
+    #[inline]
+    fn from(value: ThinBox<T, A>) -> Self {
+        value.into_pin()
+    }
+}
@@ -0,0 +1,85 @@
+use crate::ThinBoxError;
+use std::{
+    alloc::{AllocError, Allocator, Layout},
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ptr::NonNull,
+};
+
+/// Allocates a block for a header `H` followed by a value with the given layout, and writes the header right before the value.
+/// The returned pointer points to the (uninitialized) value.
+pub(crate) unsafe fn try_allocate_with_header<H>(
+    header: H,
+    layout: Layout,
+    allocate: impl FnOnce(Layout) -> Result<NonNull<[u8]>, AllocError>,
+) -> Result<NonNull<u8>, ThinBoxError> {
+    let metadata = Layout::new::<H>();
+    let (layout, offset) = match metadata.extend(layout) {
+        Ok(x) => x,
+        Err(_) => {
+            return Err(ThinBoxError::LayoutOverflow {
+                metadata,
+                value: layout,
+            })
+        }
+    };
+
+    let ptr = match allocate(layout) {
+        Ok(x) => x.as_ptr().cast::<u8>().add(offset),
+        Err(_) => return Err(ThinBoxError::AllocError { layout }),
+    };
+    debug_assert!(!ptr.is_null());
+
+    let ptr = NonNull::new_unchecked(ptr);
+    header_of::<H>(ptr).write(header);
+    return Ok(ptr);
+}
+
+/// Releases a block returned by [`try_allocate_with_header`] without dropping its value.
+pub(crate) unsafe fn deallocate_with_header<H, A: Allocator>(
+    ptr: NonNull<u8>,
+    layout: Layout,
+    alloc: &A,
+) {
+    let (layout, offset) = Layout::new::<H>().extend(layout).unwrap_unchecked();
+    alloc.deallocate(NonNull::new_unchecked(ptr.as_ptr().sub(offset)), layout);
+}
+
+/// Returns a pointer to the header that sits right before the value.
+/// Since a header's size is a multiple of its alignment, this is always inside the padding that [`Layout::extend`] adds before the value.
+#[inline]
+pub(crate) unsafe fn header_of<H>(ptr: NonNull<u8>) -> NonNull<H> {
+    return NonNull::new_unchecked(ptr.as_ptr().sub(core::mem::size_of::<H>()).cast());
+}
+
+/// Releases a block returned by [`try_allocate_with_header`] (without dropping its value) unless forgotten.
+pub(crate) struct AllocGuard<'a, H, A: Allocator> {
+    ptr: NonNull<u8>,
+    layout: Layout,
+    alloc: &'a A,
+    _phtm: PhantomData<H>,
+}
+
+impl<'a, H, A: Allocator> AllocGuard<'a, H, A> {
+    #[inline]
+    pub(crate) unsafe fn new(ptr: NonNull<u8>, layout: Layout, alloc: &'a A) -> Self {
+        return Self {
+            ptr,
+            layout,
+            alloc,
+            _phtm: PhantomData,
+        };
+    }
+
+    #[inline]
+    pub(crate) fn forget(self) {
+        let _ = ManuallyDrop::new(self);
+    }
+}
+
+impl<'a, H, A: Allocator> Drop for AllocGuard<'a, H, A> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { deallocate_with_header::<H, A>(self.ptr, self.layout, self.alloc) }
+    }
+}
@@ -0,0 +1,500 @@
+use crate::{
+    raw::{deallocate_with_header, header_of, try_allocate_with_header, AllocGuard},
+    CloneThin, ThinBoxError,
+};
+use std::{
+    alloc::{Allocator, Global, Layout},
+    borrow::Borrow,
+    cell::Cell,
+    fmt::{Debug, Display},
+    hash::Hash,
+    marker::{PhantomData, Unsize},
+    mem::ManuallyDrop,
+    ops::Deref,
+    ptr::{NonNull, Pointee},
+};
+
+/// Header of a [`ThinRc`] allocation, placed right before the value.
+#[repr(C)]
+pub(crate) struct RcHeader<M> {
+    pub(crate) strong: Cell<usize>,
+    pub(crate) weak: Cell<usize>,
+    pub(crate) meta: M,
+}
+
+/// Single-threaded reference-counted pointer that is a single pointer wide, even for unsized values.
+/// The reference counts are stored next to the pointee metadata, right before the value.
+pub struct ThinRc<T: ?Sized, A: Allocator = Global> {
+    pub(crate) ptr: NonNull<u8>,
+    alloc: A,
+    _phtm: PhantomData<T>,
+}
+
+impl<T> ThinRc<T> {
+    #[inline]
+    pub fn new(v: T) -> Self {
+        Self::new_in(v, Global)
+    }
+
+    #[inline]
+    pub fn try_new(v: T) -> Result<Self, ThinBoxError> {
+        Self::try_new_in(v, Global)
+    }
+}
+
+impl<T: ?Sized> ThinRc<T> {
+    #[inline]
+    pub fn new_unsize<U: Unsize<T>>(v: U) -> Self {
+        Self::new_unsize_in(v, Global)
+    }
+
+    #[inline]
+    pub fn try_new_unsize<U: Unsize<T>>(v: U) -> Result<Self, ThinBoxError> {
+        Self::try_new_unsize_in(v, Global)
+    }
+
+    #[inline]
+    pub fn into_raw(self) -> NonNull<()> {
+        self.into_raw_with_alloc().0
+    }
+
+    /// # Safety
+    /// `ptr` must have been returned by [`into_raw`](ThinRc::into_raw) for the same `T`, and each call takes over one strong reference.
+    #[inline]
+    pub unsafe fn from_raw(ptr: NonNull<()>) -> Self {
+        return Self::from_raw_with_alloc(ptr, Global);
+    }
+}
+
+impl<T, A: Allocator> ThinRc<T, A> {
+    #[inline]
+    pub fn new_in(v: T, alloc: A) -> Self {
+        Self::try_new_in(v, alloc).expect("error allocating thin value")
+    }
+
+    #[inline]
+    pub fn try_new_in(v: T, alloc: A) -> Result<Self, ThinBoxError> {
+        unsafe { Self::try_new_by_parts_in((), v, alloc) }
+    }
+
+    /// Returns the inner value if this is the only strong reference to it.
+    pub fn try_unwrap(this: Self) -> Result<T, Self> {
+        if this.header().strong.get() != 1 {
+            return Err(this);
+        }
+
+        unsafe {
+            let this = ManuallyDrop::new(this);
+            let value = core::ptr::read(this.ptr.as_ptr().cast::<T>());
+            this.header().strong.set(0);
+            this.release_weak();
+            core::ptr::drop_in_place(&this.alloc as *const A as *mut A);
+            return Ok(value);
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> ThinRc<T, A> {
+    #[inline]
+    pub fn new_unsize_in<U: Unsize<T>>(v: U, alloc: A) -> Self {
+        Self::try_new_unsize_in(v, alloc).expect("error allocating thin value")
+    }
+
+    #[inline]
+    pub fn try_new_unsize_in<U: Unsize<T>>(v: U, alloc: A) -> Result<Self, ThinBoxError> {
+        unsafe { Self::try_new_by_parts_in(core::ptr::metadata(&v as &T), v, alloc) }
+    }
+
+    unsafe fn try_new_by_parts_in<U>(
+        meta: <T as Pointee>::Metadata,
+        v: U,
+        alloc: A,
+    ) -> Result<Self, ThinBoxError> {
+        let ptr = Self::try_allocate_in(meta, Layout::new::<U>(), &alloc)?;
+        ptr.as_ptr().cast::<U>().write(v);
+        return Ok(Self::from_raw_with_alloc(ptr.cast(), alloc));
+    }
+
+    unsafe fn try_allocate_in(
+        meta: <T as Pointee>::Metadata,
+        layout: Layout,
+        alloc: &A,
+    ) -> Result<NonNull<u8>, ThinBoxError> {
+        let header = RcHeader {
+            strong: Cell::new(1),
+            weak: Cell::new(1),
+            meta,
+        };
+        try_allocate_with_header(header, layout, |layout| alloc.allocate(layout))
+    }
+
+    #[inline]
+    pub fn into_raw_with_alloc(self) -> (NonNull<()>, A) {
+        let this = ManuallyDrop::new(self);
+        return unsafe { (this.ptr.cast(), core::ptr::read(&this.alloc)) };
+    }
+
+    /// # Safety
+    /// `ptr` must have been returned by [`into_raw_with_alloc`](ThinRc::into_raw_with_alloc) for the same `T`, `alloc` must be able to release its block,
+    /// and each call takes over one strong reference.
+    #[inline]
+    pub unsafe fn from_raw_with_alloc(ptr: NonNull<()>, alloc: A) -> Self {
+        return Self {
+            ptr: ptr.cast(),
+            alloc,
+            _phtm: PhantomData,
+        };
+    }
+
+    #[inline]
+    pub fn metadata(&self) -> <T as Pointee>::Metadata {
+        self.header().meta
+    }
+
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        return &self.alloc;
+    }
+
+    #[inline]
+    pub fn heap_layout(&self) -> Layout {
+        unsafe {
+            return Layout::new::<RcHeader<<T as Pointee>::Metadata>>()
+                .extend(self.value_layout())
+                .unwrap_unchecked()
+                .0;
+        }
+    }
+
+    #[inline]
+    pub fn strong_count(this: &Self) -> usize {
+        this.header().strong.get()
+    }
+
+    #[inline]
+    pub fn weak_count(this: &Self) -> usize {
+        this.header().weak.get() - 1
+    }
+
+    /// Creates a new [`ThinWeak`] pointer to this allocation.
+    #[inline]
+    pub fn downgrade(this: &Self) -> ThinWeak<T, A>
+    where
+        A: Clone,
+    {
+        let weak = this.header().weak.get();
+        if weak == isize::MAX as usize {
+            std::process::abort();
+        }
+
+        this.header().weak.set(weak + 1);
+        return ThinWeak {
+            ptr: this.ptr,
+            alloc: this.alloc.clone(),
+            _phtm: PhantomData,
+        };
+    }
+
+    /// Returns `true` if both pointers point to the same allocation.
+    #[inline]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        this.ptr == other.ptr
+    }
+
+    /// Returns a mutable reference to the value if there are no other references to it.
+    #[inline]
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        let header = this.header();
+        if header.strong.get() == 1 && header.weak.get() == 1 {
+            return Some(unsafe { &mut *this.value_ptr() });
+        }
+        return None;
+    }
+
+    /// Returns a mutable reference to the value, cloning it into a new allocation if there are other references to it.
+    pub fn make_mut(this: &mut Self) -> &mut T
+    where
+        T: CloneThin,
+        A: Clone,
+    {
+        unsafe {
+            let layout = this.value_layout();
+            if this.header().strong.get() != 1 {
+                let ptr = Self::try_allocate_in(this.metadata(), layout, &this.alloc)
+                    .expect("error allocating thin value");
+
+                let guard = AllocGuard::<RcHeader<<T as Pointee>::Metadata>, A>::new(ptr, layout, &this.alloc);
+                T::clone_into_raw(this, ptr.as_ptr());
+                guard.forget();
+
+                *this = Self::from_raw_with_alloc(ptr.cast(), this.alloc.clone());
+            } else if this.header().weak.get() != 1 {
+                // Only weak references are left, so the value can be moved out of their allocation.
+                let ptr = Self::try_allocate_in(this.metadata(), layout, &this.alloc)
+                    .expect("error allocating thin value");
+
+                core::ptr::copy_nonoverlapping(this.ptr.as_ptr(), ptr.as_ptr(), layout.size());
+                this.header().strong.set(0);
+                this.release_weak();
+                this.ptr = ptr;
+            }
+
+            return &mut *this.value_ptr();
+        }
+    }
+
+    #[inline]
+    pub(crate) fn header(&self) -> &RcHeader<<T as Pointee>::Metadata> {
+        unsafe { header_of(self.ptr).as_ref() }
+    }
+
+    #[inline]
+    fn value_ptr(&self) -> *mut T {
+        core::ptr::from_raw_parts_mut(self.ptr.as_ptr(), self.metadata())
+    }
+
+    #[inline]
+    fn value_layout(&self) -> Layout {
+        unsafe { Layout::for_value_raw(self.value_ptr()) }
+    }
+
+    #[inline]
+    unsafe fn release_weak(&self) {
+        release_weak::<T, A>(self.ptr, &self.alloc)
+    }
+}
+
+impl<T: ?Sized, A: Allocator + Clone> Clone for ThinRc<T, A> {
+    #[inline]
+    fn clone(&self) -> Self {
+        let strong = self.header().strong.get();
+        if strong == isize::MAX as usize {
+            std::process::abort();
+        }
+
+        self.header().strong.set(strong + 1);
+        return unsafe { Self::from_raw_with_alloc(self.ptr.cast(), self.alloc.clone()) };
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Drop for ThinRc<T, A> {
+    #[inline]
+    fn drop(&mut self) {
+        let strong = self.header().strong.get() - 1;
+        self.header().strong.set(strong);
+        if strong == 0 {
+            unsafe {
+                core::ptr::drop_in_place(self.value_ptr());
+                self.release_weak();
+            }
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Deref for ThinRc<T, A> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.value_ptr() }
+    }
+}
+
+impl<T: ?Sized + Debug, A: Allocator> Debug for ThinRc<T, A> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        T::fmt(self, f)
+    }
+}
+
+impl<T: ?Sized + Display, A: Allocator> Display for ThinRc<T, A> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        T::fmt(self, f)
+    }
+}
+
+impl<T: ?Sized + PartialEq, A: Allocator, B: Allocator> PartialEq<ThinRc<T, B>> for ThinRc<T, A> {
+    #[inline]
+    fn eq(&self, other: &ThinRc<T, B>) -> bool {
+        T::eq(self, other)
+    }
+}
+
+impl<T: ?Sized + Eq, A: Allocator> Eq for ThinRc<T, A> {}
+
+impl<T: ?Sized + PartialOrd, A: Allocator, B: Allocator> PartialOrd<ThinRc<T, B>> for ThinRc<T, A> {
+    #[inline]
+    fn partial_cmp(&self, other: &ThinRc<T, B>) -> Option<std::cmp::Ordering> {
+        T::partial_cmp(self, other)
+    }
+}
+
+impl<T: ?Sized + Ord, A: Allocator> Ord for ThinRc<T, A> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        T::cmp(self, other)
+    }
+}
+
+impl<T: ?Sized + Hash, A: Allocator> Hash for ThinRc<T, A> {
+    #[inline]
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        T::hash(self, state)
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Borrow<T> for ThinRc<T, A> {
+    #[inline]
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized, A: Allocator> AsRef<T> for ThinRc<T, A> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Unpin for ThinRc<T, A> {}
+
+/// Non-owning, single pointer wide reference to the contents of a [`ThinRc`].
+/// The value is dropped with the last [`ThinRc`], but the header stays allocated until the last `ThinWeak` is dropped.
+pub struct ThinWeak<T: ?Sized, A: Allocator = Global> {
+    ptr: NonNull<u8>,
+    alloc: A,
+    _phtm: PhantomData<T>,
+}
+
+impl<T: ?Sized> ThinWeak<T> {
+    /// Creates a dangling weak pointer that never upgrades. This doesn't allocate.
+    #[inline]
+    pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+}
+
+impl<T: ?Sized, A: Allocator> ThinWeak<T, A> {
+    /// Creates a dangling weak pointer that never upgrades. This doesn't allocate.
+    #[inline]
+    pub fn new_in(alloc: A) -> Self {
+        return Self {
+            ptr: dangling(),
+            alloc,
+            _phtm: PhantomData,
+        };
+    }
+
+    /// Attempts to get a strong reference to the value, returning `None` if it has already been dropped.
+    #[inline]
+    pub fn upgrade(&self) -> Option<ThinRc<T, A>>
+    where
+        A: Clone,
+    {
+        let header = self.header()?;
+        let strong = header.strong.get();
+        if strong == 0 {
+            return None;
+        } else if strong == isize::MAX as usize {
+            std::process::abort();
+        }
+
+        header.strong.set(strong + 1);
+        return Some(unsafe { ThinRc::from_raw_with_alloc(self.ptr.cast(), self.alloc.clone()) });
+    }
+
+    #[inline]
+    pub fn strong_count(&self) -> usize {
+        self.header().map_or(0, |header| header.strong.get())
+    }
+
+    /// Returns the number of weak references to the allocation, or zero if there are no strong references left.
+    #[inline]
+    pub fn weak_count(&self) -> usize {
+        match self.header() {
+            Some(header) if header.strong.get() > 0 => header.weak.get() - 1,
+            _ => 0,
+        }
+    }
+
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        return &self.alloc;
+    }
+
+    /// Returns `true` if both pointers point to the same allocation, or if both are dangling.
+    #[inline]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.ptr == other.ptr
+    }
+
+    #[inline]
+    fn header(&self) -> Option<&RcHeader<<T as Pointee>::Metadata>> {
+        if self.ptr == dangling() {
+            return None;
+        }
+        return Some(unsafe { header_of(self.ptr).as_ref() });
+    }
+}
+
+impl<T: ?Sized, A: Allocator + Clone> Clone for ThinWeak<T, A> {
+    #[inline]
+    fn clone(&self) -> Self {
+        if let Some(header) = self.header() {
+            header.weak.set(header.weak.get() + 1);
+        }
+
+        return Self {
+            ptr: self.ptr,
+            alloc: self.alloc.clone(),
+            _phtm: PhantomData,
+        };
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Drop for ThinWeak<T, A> {
+    #[inline]
+    fn drop(&mut self) {
+        if self.ptr != dangling() {
+            unsafe { release_weak::<T, A>(self.ptr, &self.alloc) }
+        }
+    }
+}
+
+impl<T: ?Sized> Default for ThinWeak<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Debug for ThinWeak<T, A> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("(ThinWeak)")
+    }
+}
+
+/// Address used by weak pointers that don't point to any allocation. No header can live right before it.
+#[inline]
+pub(crate) fn dangling() -> NonNull<u8> {
+    unsafe { NonNull::new_unchecked(core::ptr::without_provenance_mut(usize::MAX)) }
+}
+
+/// Decrements the weak count, releasing the allocation if it reaches zero. The value must have already been dropped.
+unsafe fn release_weak<T: ?Sized, A: Allocator>(ptr: NonNull<u8>, alloc: &A) {
+    let header = header_of::<RcHeader<<T as Pointee>::Metadata>>(ptr).as_ref();
+    let weak = header.weak.get() - 1;
+    header.weak.set(weak);
+
+    if weak == 0 {
+        let value = core::ptr::from_raw_parts_mut::<T>(ptr.as_ptr(), header.meta);
+        deallocate_with_header::<RcHeader<<T as Pointee>::Metadata>, A>(
+            ptr,
+            Layout::for_value_raw(value),
+            alloc,
+        );
+    }
+}
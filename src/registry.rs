@@ -0,0 +1,205 @@
+use crate::{DeserializeMetadata, ThinBox};
+use std::{
+    alloc::{Allocator, Global},
+    any::TypeId,
+    collections::{BTreeMap, HashMap},
+    fmt::{Debug, Formatter},
+    marker::Unsize,
+    ptr::DynMetadata,
+};
+
+/// Concrete values that can be stored in a [`Registry`]. Implemented for every `'static` type that implements [`Serialize`](serde::Serialize).
+///
+/// Make it a supertrait of your trait, so that the trait object can be serialized with the tag of the value behind it.
+pub trait Typetag: 'static {
+    #[doc(hidden)]
+    fn concrete_type_id(&self) -> TypeId;
+    #[doc(hidden)]
+    fn as_erased_serialize(&self) -> &dyn erased_serde::Serialize;
+}
+
+impl<U: serde::Serialize + 'static> Typetag for U {
+    #[inline]
+    fn concrete_type_id(&self) -> TypeId {
+        TypeId::of::<U>()
+    }
+
+    #[inline]
+    fn as_erased_serialize(&self) -> &dyn erased_serde::Serialize {
+        self
+    }
+}
+
+/// Trait objects whose [`ThinBox`]es are deserialized through a [`Registry`].
+///
+/// ```ignore
+/// trait Handler: Typetag { /* ... */ }
+///
+/// static HANDLERS: LazyLock<Registry<dyn Handler>> = LazyLock::new(|| {
+///     let mut registry = Registry::new();
+///     registry.register::<Ping>("ping").register::<Echo>("echo");
+///     registry
+/// });
+///
+/// impl Registered for dyn Handler {
+///     fn registry() -> &'static Registry<Self> { &HANDLERS }
+/// }
+///
+/// impl Serialize for dyn Handler {
+///     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+///         HANDLERS.serialize(self, serializer)
+///     }
+/// }
+/// ```
+pub trait Registered<A: Allocator + 'static = Global>: Typetag {
+    fn registry() -> &'static Registry<Self, A>;
+}
+
+type DeserializeFn<T, A> = for<'de> fn(&mut dyn erased_serde::Deserializer<'de>, A) -> Result<ThinBox<T, A>, erased_serde::Error>;
+
+/// Maps string tags to the concrete types behind a trait object `T`.
+///
+/// Values are serialized externally tagged, as a single-entry map from their tag to their contents,
+/// and deserialized directly into a `ThinBox<T, A>` with [`new_unsize_in`](ThinBox::new_unsize_in).
+pub struct Registry<T: ?Sized, A: Allocator = Global> {
+    tags: HashMap<TypeId, &'static str>,
+    entries: BTreeMap<&'static str, DeserializeFn<T, A>>,
+}
+
+impl<T: ?Sized, A: Allocator> Registry<T, A> {
+    #[inline]
+    pub fn new() -> Self {
+        return Self {
+            tags: HashMap::new(),
+            entries: BTreeMap::new(),
+        };
+    }
+
+    /// Registers `U` under `tag`, panicking if either of them was already registered.
+    pub fn register<U>(&mut self, tag: &'static str) -> &mut Self
+    where
+        U: Unsize<T> + serde::de::DeserializeOwned + 'static,
+    {
+        assert!(!self.entries.contains_key(tag), "tag `{tag}` is already registered");
+        if let Some(prev) = self.tags.insert(TypeId::of::<U>(), tag) {
+            panic!("type `{}` is already registered as `{prev}`", core::any::type_name::<U>());
+        }
+
+        self.entries.insert(tag, deserialize_entry::<T, U, A>);
+        return self;
+    }
+
+    /// Returns the tag of the concrete type behind `value`, if it's registered.
+    #[inline]
+    pub fn tag_of(&self, value: &T) -> Option<&'static str>
+    where
+        T: Typetag,
+    {
+        self.tags.get(&value.concrete_type_id()).copied()
+    }
+
+    /// Returns the registered tags, in order.
+    #[inline]
+    pub fn tags(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.entries.keys().copied()
+    }
+
+    /// Serializes `value` as a single-entry map from its tag to its contents.
+    pub fn serialize<S: serde::Serializer>(&self, value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Typetag,
+    {
+        use serde::{ser::Error, ser::SerializeMap};
+
+        let Some(tag) = self.tag_of(value) else {
+            return Err(S::Error::custom("the value's type isn't registered"));
+        };
+
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(tag, value.as_erased_serialize())?;
+        return map.end();
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Default for Registry<T, A> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Debug for Registry<T, A> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.entries.keys()).finish()
+    }
+}
+
+fn deserialize_entry<T: ?Sized, U: Unsize<T> + serde::de::DeserializeOwned, A: Allocator>(
+    deserializer: &mut dyn erased_serde::Deserializer<'_>,
+    alloc: A,
+) -> Result<ThinBox<T, A>, erased_serde::Error> {
+    let v = erased_serde::deserialize::<U>(deserializer)?;
+    return Ok(ThinBox::new_unsize_in(v, alloc));
+}
+
+impl<'de, T: ?Sized + Registered<A>, A: Allocator + 'static> DeserializeMetadata<'de, T, A> for DynMetadata<T> {
+    #[inline]
+    fn deserialize_in<D: serde::Deserializer<'de>>(deserializer: D, alloc: A) -> Result<ThinBox<T, A>, D::Error> {
+        deserializer.deserialize_map(RegistryVisitor {
+            registry: T::registry(),
+            alloc,
+        })
+    }
+}
+
+struct RegistryVisitor<'a, T: ?Sized, A: Allocator> {
+    registry: &'a Registry<T, A>,
+    alloc: A,
+}
+
+impl<'de, T: ?Sized, A: Allocator> serde::de::Visitor<'de> for RegistryVisitor<'_, T, A> {
+    type Value = ThinBox<T, A>;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str("a map from a registered type tag to its contents")
+    }
+
+    fn visit_map<M: serde::de::MapAccess<'de>>(self, mut map: M) -> Result<Self::Value, M::Error> {
+        use serde::de::Error;
+
+        let Some(tag) = map.next_key::<String>()? else {
+            return Err(M::Error::invalid_length(0, &self));
+        };
+
+        let Some(&f) = self.registry.entries.get(&*tag) else {
+            return Err(M::Error::custom(format_args!("unknown type tag `{tag}`")));
+        };
+
+        let v = map.next_value_seed(EntrySeed {
+            f,
+            alloc: self.alloc,
+        })?;
+
+        if map.next_key::<serde::de::IgnoredAny>()?.is_some() {
+            return Err(M::Error::custom("expected a single type tag"));
+        }
+        return Ok(v);
+    }
+}
+
+struct EntrySeed<T: ?Sized, A: Allocator> {
+    f: DeserializeFn<T, A>,
+    alloc: A,
+}
+
+impl<'de, T: ?Sized, A: Allocator> serde::de::DeserializeSeed<'de> for EntrySeed<T, A> {
+    type Value = ThinBox<T, A>;
+
+    #[inline]
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        use serde::de::Error;
+        let mut erased = <dyn erased_serde::Deserializer>::erase(deserializer);
+        (self.f)(&mut erased, self.alloc).map_err(D::Error::custom)
+    }
+}
@@ -1,8 +1,10 @@
 #![allow(unused_imports)]
 
-use std::alloc::Allocator;
+use std::{alloc::{Allocator, Global}, ffi::CStr, fmt::Formatter, marker::PhantomData, path::Path, ptr::Pointee};
 use docfg::docfg;
-use crate::ThinBox;
+use crate::{slice::SliceWriter, ThinBox};
+#[cfg(feature = "serde")]
+use crate::slice::MAX_PREALLOC_BYTES;
 
 #[docfg(feature = "serde")]
 impl<T: ?Sized + serde::Serialize, A: Allocator> serde::Serialize for ThinBox<T, A> {
@@ -13,10 +15,247 @@ impl<T: ?Sized + serde::Serialize, A: Allocator> serde::Serialize for ThinBox<T,
 }
 
 #[docfg(feature = "serde")]
-impl<'de, T: serde::Deserialize<'de>, A: Allocator + Default> serde::Deserialize<'de> for ThinBox<T, A> {
+impl<'de, T: ?Sized, A: Allocator + Default> serde::Deserialize<'de> for ThinBox<T, A> where ThinBoxSeed<T, A>: serde::de::DeserializeSeed<'de, Value = Self> {
     #[inline]
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: serde::Deserializer<'de> {
+        serde::de::DeserializeSeed::deserialize(ThinBoxSeed::new(Default::default()), deserializer)
+    }
+}
+
+/// Deserializes a [`ThinBox`] into the given allocator, so that allocators without a [`Default`] implementation can be used.
+///
+/// Sized contents are deserialized as usual and then moved into the box, while strings, slices, paths and C strings are written straight into it.
+/// Trait objects are built through their [`Registry`](crate::Registry), with the `registry` feature.
+#[docfg(feature = "serde")]
+pub struct ThinBoxSeed<T: ?Sized, A: Allocator = Global> {
+    alloc: A,
+    _phtm: PhantomData<fn() -> ThinBox<T, A>>,
+}
+
+#[cfg(feature = "serde")]
+impl<T: ?Sized, A: Allocator> ThinBoxSeed<T, A> {
+    #[inline]
+    pub fn new(alloc: A) -> Self {
+        return Self { alloc, _phtm: PhantomData };
+    }
+
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        return &self.alloc;
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: ?Sized, A: Allocator> serde::de::DeserializeSeed<'de> for ThinBoxSeed<T, A> where <T as Pointee>::Metadata: DeserializeMetadata<'de, T, A> {
+    type Value = ThinBox<T, A>;
+
+    #[inline]
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error> where D: serde::Deserializer<'de> {
+        <<T as Pointee>::Metadata as DeserializeMetadata<'de, T, A>>::deserialize_in(deserializer, self.alloc)
+    }
+}
+
+/// Picks how a [`ThinBox`] is deserialized from the kind of its contents' metadata: sized values, slice-like values or trait objects.
+/// A single seed implementation dispatching through this trait avoids overlapping implementations for each kind.
+#[doc(hidden)]
+#[cfg(feature = "serde")]
+pub trait DeserializeMetadata<'de, T: ?Sized, A: Allocator> {
+    fn deserialize_in<D: serde::Deserializer<'de>>(deserializer: D, alloc: A) -> Result<ThinBox<T, A>, D::Error>;
+}
+
+/// Unsized types with `usize` metadata that can be deserialized straight into a [`ThinBox`].
+#[doc(hidden)]
+#[cfg(feature = "serde")]
+pub trait DeserializeUnsized<'de>: Pointee<Metadata = usize> {
+    fn deserialize_in<D: serde::Deserializer<'de>, A: Allocator>(deserializer: D, alloc: A) -> Result<ThinBox<Self, A>, D::Error>;
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>, A: Allocator> DeserializeMetadata<'de, T, A> for () {
+    #[inline]
+    fn deserialize_in<D: serde::Deserializer<'de>>(deserializer: D, alloc: A) -> Result<ThinBox<T, A>, D::Error> {
         let v = T::deserialize(deserializer)?;
-        return Ok(Self::new_in(v, Default::default()))
+        return Ok(ThinBox::new_in(v, alloc))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: ?Sized + DeserializeUnsized<'de>, A: Allocator> DeserializeMetadata<'de, T, A> for usize {
+    #[inline]
+    fn deserialize_in<D: serde::Deserializer<'de>>(deserializer: D, alloc: A) -> Result<ThinBox<T, A>, D::Error> {
+        T::deserialize_in(deserializer, alloc)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> DeserializeUnsized<'de> for str {
+    #[inline]
+    fn deserialize_in<D: serde::Deserializer<'de>, A: Allocator>(deserializer: D, alloc: A) -> Result<ThinBox<Self, A>, D::Error> {
+        deserializer.deserialize_str(StrVisitor(alloc))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> DeserializeUnsized<'de> for [T] {
+    #[inline]
+    fn deserialize_in<D: serde::Deserializer<'de>, A: Allocator>(deserializer: D, alloc: A) -> Result<ThinBox<Self, A>, D::Error> {
+        deserializer.deserialize_seq(SliceVisitor(alloc, PhantomData))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> DeserializeUnsized<'de> for Path {
+    #[inline]
+    fn deserialize_in<D: serde::Deserializer<'de>, A: Allocator>(deserializer: D, alloc: A) -> Result<ThinBox<Self, A>, D::Error> {
+        deserializer.deserialize_str(PathVisitor(alloc))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> DeserializeUnsized<'de> for CStr {
+    #[inline]
+    fn deserialize_in<D: serde::Deserializer<'de>, A: Allocator>(deserializer: D, alloc: A) -> Result<ThinBox<Self, A>, D::Error> {
+        deserializer.deserialize_byte_buf(CStrVisitor(alloc))
+    }
+}
+
+/// Builds a thin slice from a sequence, writing straight into a block of the hinted length and falling back to a [`Vec`] if there's no hint or it was too small.
+#[cfg(feature = "serde")]
+struct SliceVisitor<T, A>(A, PhantomData<T>);
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>, A: Allocator> serde::de::Visitor<'de> for SliceVisitor<T, A> {
+    type Value = ThinBox<[T], A>;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str("a sequence")
     }
-}
\ No newline at end of file
+
+    fn visit_seq<S>(self, mut seq: S) -> Result<Self::Value, S::Error> where S: serde::de::SeqAccess<'de> {
+        use serde::de::Error;
+
+        let alloc = self.0;
+        let hint = match seq.size_hint() {
+            Some(x) => x.min(MAX_PREALLOC_BYTES / core::mem::size_of::<T>().max(1)),
+            None => return Ok(ThinBox::from_vec_in(collect_seq(Vec::new(), seq)?, alloc)),
+        };
+
+        let mut writer = SliceWriter::try_new(hint, &alloc).map_err(S::Error::custom)?;
+        while writer.len < writer.cap {
+            match seq.next_element()? {
+                Some(x) => unsafe { writer.push_unchecked(x) },
+                None => {
+                    let ptr = writer.finish_shrink().map_err(S::Error::custom)?;
+                    return Ok(unsafe { ThinBox::from_raw_with_alloc(ptr, alloc) });
+                }
+            }
+        }
+
+        let Some(next) = seq.next_element()? else {
+            let ptr = writer.finish();
+            return Ok(unsafe { ThinBox::from_raw_with_alloc(ptr, alloc) });
+        };
+
+        // the hint was too small, so the elements are moved into a vector
+        let mut v = Vec::with_capacity(writer.len + 1 + seq.size_hint().unwrap_or(0));
+        unsafe {
+            core::ptr::copy_nonoverlapping(writer.elems.as_ptr(), v.as_mut_ptr(), writer.len);
+            v.set_len(writer.len);
+            writer.len = 0;
+        }
+
+        drop(writer);
+        v.push(next);
+        return Ok(ThinBox::from_vec_in(collect_seq(v, seq)?, alloc));
+    }
+}
+
+#[cfg(feature = "serde")]
+#[inline]
+fn collect_seq<'de, T: serde::Deserialize<'de>, S: serde::de::SeqAccess<'de>>(mut v: Vec<T>, mut seq: S) -> Result<Vec<T>, S::Error> {
+    while let Some(x) = seq.next_element()? {
+        v.push(x);
+    }
+    return Ok(v);
+}
+
+#[cfg(feature = "serde")]
+struct StrVisitor<A>(A);
+
+#[cfg(feature = "serde")]
+impl<'de, A: Allocator> serde::de::Visitor<'de> for StrVisitor<A> {
+    type Value = ThinBox<str, A>;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str("a string")
+    }
+
+    #[inline]
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> where E: serde::de::Error {
+        Ok(ThinBox::from_str_in(v, self.0))
+    }
+
+    #[inline]
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E> where E: serde::de::Error {
+        match core::str::from_utf8(v) {
+            Ok(s) => self.visit_str(s),
+            Err(_) => Err(E::invalid_value(serde::de::Unexpected::Bytes(v), &self)),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+struct PathVisitor<A>(A);
+
+#[cfg(feature = "serde")]
+impl<'de, A: Allocator> serde::de::Visitor<'de> for PathVisitor<A> {
+    type Value = ThinBox<Path, A>;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str("path string")
+    }
+
+    #[inline]
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> where E: serde::de::Error {
+        Ok(ThinBox::from_path_in(Path::new(v), self.0))
+    }
+
+    #[inline]
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E> where E: serde::de::Error {
+        match core::str::from_utf8(v) {
+            Ok(s) => self.visit_str(s),
+            Err(_) => Err(E::invalid_value(serde::de::Unexpected::Bytes(v), &self)),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+struct CStrVisitor<A>(A);
+
+#[cfg(feature = "serde")]
+impl<'de, A: Allocator> serde::de::Visitor<'de> for CStrVisitor<A> {
+    type Value = ThinBox<CStr, A>;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str("a byte string without interior NUL bytes")
+    }
+
+    #[inline]
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E> where E: serde::de::Error {
+        match v.iter().position(|&x| x == 0) {
+            Some(i) => Err(E::custom(format_args!("nul byte found in provided data at position: {i}"))),
+            None => Ok(unsafe { ThinBox::from_bytes_unchecked_in(v, self.0) }),
+        }
+    }
+
+    #[inline]
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> where E: serde::de::Error {
+        self.visit_bytes(v.as_bytes())
+    }
+
+    #[inline]
+    fn visit_seq<S>(self, seq: S) -> Result<Self::Value, S::Error> where S: serde::de::SeqAccess<'de> {
+        let v = collect_seq::<u8, S>(Vec::new(), seq)?;
+        self.visit_bytes(&v)
+    }
+}
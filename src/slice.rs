@@ -0,0 +1,301 @@
+use crate::{ThinBox, ThinBoxError};
+use std::{
+    alloc::{Allocator, Global, Layout},
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ptr::{NonNull, Pointee},
+};
+
+impl<T> ThinBox<[T]> {
+    #[inline]
+    pub fn from_slice(v: &[T]) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_slice_in(v, Global)
+    }
+
+    #[inline]
+    pub fn from_vec(v: Vec<T>) -> Self {
+        Self::from_vec_in(v, Global)
+    }
+
+    #[inline]
+    pub fn from_fn<F: FnMut(usize) -> T>(len: usize, f: F) -> Self {
+        Self::from_fn_in(len, f, Global)
+    }
+
+    #[inline]
+    pub fn repeat(value: T, n: usize) -> Self
+    where
+        T: Clone,
+    {
+        Self::repeat_in(value, n, Global)
+    }
+
+    #[inline]
+    pub fn concat(slices: &[&[T]]) -> Self
+    where
+        T: Clone,
+    {
+        Self::concat_in(slices, Global)
+    }
+}
+
+impl<T, A: Allocator> ThinBox<[T], A> {
+    /// Collects the iterator into a new slice. If the iterator reports an exact size hint, the elements are written straight into the heap block.
+    pub fn from_iter_in<I: IntoIterator<Item = T>>(iter: I, alloc: A) -> Self {
+        let iter = iter.into_iter();
+        match iter.size_hint() {
+            (min, Some(max)) if min == max => {
+                let mut writer = SliceWriter::new(min, &alloc);
+                iter.take(min).for_each(|x| unsafe { writer.push_unchecked(x) });
+                let ptr = writer.finish_shrink().expect("error allocating thin value");
+                unsafe { Self::from_raw_with_alloc(ptr, alloc) }
+            }
+            _ => Self::from_vec_in(iter.collect::<Vec<_>>(), alloc),
+        }
+    }
+
+    #[inline]
+    pub fn from_slice_in(v: &[T], alloc: A) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_iter_in(v.iter().cloned(), alloc)
+    }
+
+    #[inline]
+    pub(crate) fn copy_from_slice_in(v: &[T], alloc: A) -> Self
+    where
+        T: Copy,
+    {
+        let mut writer = SliceWriter::new(v.len(), &alloc);
+        unsafe { writer.copy_from_slice(v) };
+        let ptr = writer.finish();
+        return unsafe { Self::from_raw_with_alloc(ptr, alloc) };
+    }
+
+    /// Moves the elements of the vector into a new thin slice, releasing the vector's buffer.
+    pub fn from_vec_in<B: Allocator>(mut v: Vec<T, B>, alloc: A) -> Self {
+        let mut writer = SliceWriter::new(v.len(), &alloc);
+        unsafe { writer.move_from_vec(&mut v) };
+        let ptr = writer.finish();
+        return unsafe { Self::from_raw_with_alloc(ptr, alloc) };
+    }
+
+    /// Creates a new slice of length `len`, where each element is produced by calling `f` with its index.
+    pub fn from_fn_in<F: FnMut(usize) -> T>(len: usize, mut f: F, alloc: A) -> Self {
+        let mut writer = SliceWriter::new(len, &alloc);
+        for i in 0..len {
+            unsafe { writer.push_unchecked(f(i)) }
+        }
+
+        let ptr = writer.finish();
+        return unsafe { Self::from_raw_with_alloc(ptr, alloc) };
+    }
+
+    /// Creates a new slice with `n` copies of `value`.
+    pub fn repeat_in(value: T, n: usize, alloc: A) -> Self
+    where
+        T: Clone,
+    {
+        let mut writer = SliceWriter::new(n, &alloc);
+        if n > 0 {
+            for _ in 1..n {
+                unsafe { writer.push_unchecked(value.clone()) }
+            }
+            unsafe { writer.push_unchecked(value) }
+        }
+
+        let ptr = writer.finish();
+        return unsafe { Self::from_raw_with_alloc(ptr, alloc) };
+    }
+
+    /// Concatenates the slices into a single thin slice.
+    pub fn concat_in(slices: &[&[T]], alloc: A) -> Self
+    where
+        T: Clone,
+    {
+        let len = slices
+            .iter()
+            .try_fold(0usize, |acc, x| acc.checked_add(x.len()))
+            .expect("capacity overflow");
+
+        let mut writer = SliceWriter::new(len, &alloc);
+        for x in slices.iter().flat_map(|x| x.iter()) {
+            unsafe { writer.push_unchecked(x.clone()) }
+        }
+
+        let ptr = writer.finish();
+        return unsafe { Self::from_raw_with_alloc(ptr, alloc) };
+    }
+}
+
+/// Maximum number of bytes preallocated from a length read from untrusted input (like a size hint or a length prefix),
+/// so that a bogus length can't request a huge block up front.
+#[cfg(any(feature = "borsh", feature = "bincode", feature = "serde"))]
+pub(crate) const MAX_PREALLOC_BYTES: usize = 1024 * 1024;
+
+/// Layout of a `#[repr(C)]` value made of a sized prefix followed by `len` elements, and the offset of the first element.
+#[inline]
+pub(crate) fn tail_layout<T>(prefix: Layout, len: usize) -> Result<(Layout, usize), ThinBoxError> {
+    let value = Layout::array::<T>(len).map_err(|_| ThinBoxError::CapacityOverflow)?;
+    let (layout, offset) = prefix
+        .extend(value)
+        .map_err(|_| ThinBoxError::LayoutOverflow { metadata: prefix, value })?;
+
+    return Ok((layout.pad_to_align(), offset));
+}
+
+/// Allocates a `U` whose tail of `len` elements starts at `offset`, with `len` as its metadata, and fills the tail through `f`.
+/// The rest of the value is left uninitialized. If `f` panics, the elements written so far are dropped and the block is released.
+///
+/// Callers write the rest of the value only after this returns, so that a panic in `f` never has to drop it.
+pub(crate) unsafe fn write_tail<T, A, U>(
+    len: usize,
+    layout: Layout,
+    offset: usize,
+    alloc: &A,
+    f: impl FnOnce(&mut SliceWriter<'_, T, A, U>),
+) -> NonNull<u8>
+where
+    A: Allocator,
+    U: ?Sized + Pointee<Metadata = usize>,
+{
+    let value = ThinBox::<U, A>::try_allocate_in(len, layout, alloc).expect("error allocating thin value");
+    let mut writer = SliceWriter::<T, A, U>::from_raw_parts(value, layout, value.add(offset).cast(), len, alloc);
+    f(&mut writer);
+    return writer.finish().cast();
+}
+
+/// Partially initialized slice inside a thin allocation.
+/// If dropped before being finished, the initialized elements are dropped and the block is released.
+pub(crate) struct SliceWriter<'a, T, A: Allocator, U: ?Sized = [T]> {
+    pub(crate) value: NonNull<u8>,
+    pub(crate) layout: Layout,
+    pub(crate) elems: NonNull<T>,
+    pub(crate) cap: usize,
+    pub(crate) len: usize,
+    alloc: &'a A,
+    _phtm: PhantomData<NonNull<U>>,
+}
+
+impl<'a, T, A: Allocator> SliceWriter<'a, T, A> {
+    #[inline]
+    pub(crate) fn new(len: usize, alloc: &'a A) -> Self {
+        Self::try_new(len, alloc).expect("error allocating thin value")
+    }
+
+    pub(crate) fn try_new(len: usize, alloc: &'a A) -> Result<Self, ThinBoxError> {
+        let layout = Layout::array::<T>(len).map_err(|_| ThinBoxError::CapacityOverflow)?;
+        unsafe {
+            let value = ThinBox::<[T], A>::try_allocate_in(len, layout, alloc)?;
+            return Ok(Self::from_raw_parts(value, layout, value.cast(), len, alloc));
+        }
+    }
+
+    /// Finishes the slice, shrinking the allocation if fewer elements than expected were written.
+    /// If shrinking fails, the writer is dropped along with the elements written so far.
+    pub(crate) fn finish_shrink(mut self) -> Result<NonNull<()>, ThinBoxError> {
+        if self.len == self.cap {
+            return Ok(self.finish());
+        }
+
+        unsafe {
+            let header = Layout::new::<usize>();
+            let (old_layout, offset) = header.extend(self.layout).unwrap_unchecked();
+            let (new_layout, _) = header
+                .extend(Layout::array::<T>(self.len).unwrap_unchecked())
+                .unwrap_unchecked();
+
+            let block = NonNull::new_unchecked(self.value.as_ptr().sub(offset));
+            let block = match self.alloc.shrink(block, old_layout, new_layout) {
+                Ok(x) => x.cast::<u8>(),
+                Err(_) => return Err(ThinBoxError::AllocError { layout: new_layout }),
+            };
+
+            self.value = NonNull::new_unchecked(block.as_ptr().add(offset));
+            self.value
+                .as_ptr()
+                .sub(core::mem::size_of::<usize>())
+                .cast::<usize>()
+                .write(self.len);
+
+            self.layout = Layout::array::<T>(self.len).unwrap_unchecked();
+            self.cap = self.len;
+        }
+
+        return Ok(self.finish());
+    }
+}
+
+impl<'a, T, A: Allocator, U: ?Sized> SliceWriter<'a, T, A, U> {
+    /// `value` and `layout` must be the ones used to allocate the block through [`ThinBox::try_allocate_in`],
+    /// and `elems` must be valid for writes of `cap` elements.
+    #[inline]
+    pub(crate) unsafe fn from_raw_parts(
+        value: NonNull<u8>,
+        layout: Layout,
+        elems: NonNull<T>,
+        cap: usize,
+        alloc: &'a A,
+    ) -> Self {
+        return Self {
+            value,
+            layout,
+            elems,
+            cap,
+            len: 0,
+            alloc,
+            _phtm: PhantomData,
+        };
+    }
+
+    #[inline]
+    pub(crate) unsafe fn push_unchecked(&mut self, v: T) {
+        debug_assert!(self.len < self.cap);
+        self.elems.as_ptr().add(self.len).write(v);
+        self.len += 1;
+    }
+
+    #[inline]
+    pub(crate) unsafe fn copy_from_slice(&mut self, v: &[T])
+    where
+        T: Copy,
+    {
+        debug_assert!(self.cap - self.len >= v.len());
+        core::ptr::copy_nonoverlapping(v.as_ptr(), self.elems.as_ptr().add(self.len), v.len());
+        self.len += v.len();
+    }
+
+    /// Moves all the elements out of `v`, leaving it empty.
+    #[inline]
+    pub(crate) unsafe fn move_from_vec<B: Allocator>(&mut self, v: &mut Vec<T, B>) {
+        debug_assert!(self.cap - self.len >= v.len());
+        core::ptr::copy_nonoverlapping(v.as_ptr(), self.elems.as_ptr().add(self.len), v.len());
+        self.len += v.len();
+        v.set_len(0);
+    }
+
+    /// Returns the pointer to the value, leaving the elements initialized.
+    #[inline]
+    pub(crate) fn finish(self) -> NonNull<()> {
+        assert_eq!(self.len, self.cap, "slice was not fully initialized");
+        let this = ManuallyDrop::new(self);
+        return this.value.cast();
+    }
+}
+
+impl<'a, T, A: Allocator, U: ?Sized> Drop for SliceWriter<'a, T, A, U> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                self.elems.as_ptr(),
+                self.len,
+            ));
+            ThinBox::<U, A>::deallocate_raw(self.value, self.layout, self.alloc);
+        }
+    }
+}
@@ -0,0 +1,537 @@
+use crate::{slice::SliceWriter, ThinBox, ThinBoxError, ThinVec};
+use std::{
+    alloc::{Allocator, Global},
+    borrow::{Borrow, BorrowMut},
+    convert::Infallible,
+    error::Error,
+    fmt::{Debug, Display, Write},
+    hash::Hash,
+    ops::{Deref, DerefMut},
+    str::{FromStr, Utf8Error},
+};
+
+impl<A: Allocator> ThinBox<str, A> {
+    #[inline]
+    pub fn from_str_in(s: &str, alloc: A) -> Self {
+        unsafe { Self::from_utf8_unchecked(ThinBox::copy_from_slice_in(s.as_bytes(), alloc)) }
+    }
+
+    /// Collects the iterator (of `char`s, `&str`s or [`String`]s) into a new thin string slice.
+    #[inline]
+    pub fn from_iter_in<S, I: IntoIterator<Item = S>>(iter: I, alloc: A) -> Self
+    where
+        String: FromIterator<S>,
+    {
+        Self::from_str_in(&String::from_iter(iter), alloc)
+    }
+
+    /// Converts a thin byte slice into a thin string slice, reusing its allocation.
+    #[inline]
+    pub fn from_utf8(v: ThinBox<[u8], A>) -> Result<Self, FromUtf8Error<A>> {
+        match core::str::from_utf8(&v) {
+            Ok(_) => unsafe { Ok(Self::from_utf8_unchecked(v)) },
+            Err(error) => Err(FromUtf8Error { bytes: v, error }),
+        }
+    }
+
+    /// Converts a thin byte slice into a thin string slice, reusing its allocation and without checking that it contains valid UTF-8.
+    ///
+    /// # Safety
+    /// The bytes must be valid UTF-8.
+    #[inline]
+    pub unsafe fn from_utf8_unchecked(v: ThinBox<[u8], A>) -> Self {
+        let (ptr, alloc) = v.into_raw_with_alloc();
+        return Self::from_raw_with_alloc(ptr, alloc);
+    }
+
+    /// Converts the thin string slice into a thin byte slice, reusing its allocation.
+    #[inline]
+    pub fn into_boxed_bytes(self) -> ThinBox<[u8], A> {
+        let (ptr, alloc) = self.into_raw_with_alloc();
+        return unsafe { ThinBox::from_raw_with_alloc(ptr, alloc) };
+    }
+
+    pub(crate) fn concat_in<S: AsRef<str>>(parts: &[S], alloc: A) -> Self {
+        let len = parts
+            .iter()
+            .try_fold(0usize, |acc, x| acc.checked_add(x.as_ref().len()))
+            .expect("capacity overflow");
+
+        let mut writer = SliceWriter::<u8, A>::new(len, &alloc);
+        for part in parts {
+            unsafe { writer.copy_from_slice(part.as_ref().as_bytes()) }
+        }
+
+        let ptr = writer.finish();
+        return unsafe { Self::from_raw_with_alloc(ptr, alloc) };
+    }
+}
+
+impl<A: Allocator + Default> From<&str> for ThinBox<str, A> {
+    #[inline]
+    fn from(value: &str) -> Self {
+        Self::from_str_in(value, Default::default())
+    }
+}
+
+impl<A: Allocator + Default> From<String> for ThinBox<str, A> {
+    #[inline]
+    fn from(value: String) -> Self {
+        Self::from_str_in(&value, Default::default())
+    }
+}
+
+impl<A: Allocator + Default> FromStr for ThinBox<str, A> {
+    type Err = Infallible;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+impl<A: Allocator + Default> FromIterator<char> for ThinBox<str, A> {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = char>>(iter: T) -> Self {
+        Self::from_iter_in(iter, Default::default())
+    }
+}
+
+impl<'a, A: Allocator + Default> FromIterator<&'a str> for ThinBox<str, A> {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = &'a str>>(iter: T) -> Self {
+        Self::concat_in(&Vec::from_iter(iter), Default::default())
+    }
+}
+
+impl<A: Allocator + Default> FromIterator<String> for ThinBox<str, A> {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = String>>(iter: T) -> Self {
+        Self::concat_in(&Vec::from_iter(iter), Default::default())
+    }
+}
+
+impl<A: Allocator> AsRef<[u8]> for ThinBox<str, A> {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// Error returned when a thin byte slice isn't valid UTF-8. The original bytes can be recovered with [`into_bytes`](FromUtf8Error::into_bytes).
+pub struct FromUtf8Error<A: Allocator = Global> {
+    bytes: ThinBox<[u8], A>,
+    error: Utf8Error,
+}
+
+impl<A: Allocator> FromUtf8Error<A> {
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    #[inline]
+    pub fn into_bytes(self) -> ThinBox<[u8], A> {
+        self.bytes
+    }
+
+    #[inline]
+    pub fn utf8_error(&self) -> Utf8Error {
+        self.error
+    }
+}
+
+impl<A: Allocator> Debug for FromUtf8Error<A> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FromUtf8Error")
+            .field("bytes", &self.bytes)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl<A: Allocator> Display for FromUtf8Error<A> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.error, f)
+    }
+}
+
+impl<A: Allocator> Error for FromUtf8Error<A> {
+    #[inline]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Growable UTF-8 string that is a single pointer wide, built on [`ThinVec<u8, A>`](ThinVec).
+/// An empty string without capacity doesn't allocate.
+pub struct ThinString<A: Allocator = Global> {
+    vec: ThinVec<u8, A>,
+}
+
+impl ThinString {
+    #[inline]
+    pub const fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    #[inline]
+    pub fn with_capacity(cap: usize) -> Self {
+        Self::with_capacity_in(cap, Global)
+    }
+}
+
+impl<A: Allocator> ThinString<A> {
+    #[inline]
+    pub const fn new_in(alloc: A) -> Self {
+        return Self {
+            vec: ThinVec::new_in(alloc),
+        };
+    }
+
+    #[inline]
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        return Self {
+            vec: ThinVec::with_capacity_in(cap, alloc),
+        };
+    }
+
+    #[inline]
+    pub fn from_str_in(s: &str, alloc: A) -> Self {
+        let mut this = Self::with_capacity_in(s.len(), alloc);
+        this.push_str(s);
+        return this;
+    }
+
+    /// Collects the iterator (of `char`s or `&str`s) into a new string.
+    #[inline]
+    pub fn from_iter_in<S, I: IntoIterator<Item = S>>(iter: I, alloc: A) -> Self
+    where
+        Self: Extend<S>,
+    {
+        let mut this = Self::new_in(alloc);
+        this.extend(iter);
+        return this;
+    }
+
+    /// Converts a vector of bytes into a string if it's valid UTF-8.
+    #[inline]
+    pub fn from_utf8(vec: ThinVec<u8, A>) -> Result<Self, (ThinVec<u8, A>, Utf8Error)> {
+        match core::str::from_utf8(&vec) {
+            Ok(_) => Ok(Self { vec }),
+            Err(e) => Err((vec, e)),
+        }
+    }
+
+    /// Converts a vector of bytes into a string without checking that it's valid UTF-8.
+    ///
+    /// # Safety
+    /// The bytes must be valid UTF-8.
+    #[inline]
+    pub unsafe fn from_utf8_unchecked(vec: ThinVec<u8, A>) -> Self {
+        return Self { vec };
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.vec.capacity()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        unsafe { core::str::from_utf8_unchecked(&self.vec) }
+    }
+
+    #[inline]
+    pub fn as_mut_str(&mut self) -> &mut str {
+        unsafe { core::str::from_utf8_unchecked_mut(&mut self.vec) }
+    }
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.vec
+    }
+
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        self.vec.allocator()
+    }
+
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.vec.reserve(additional)
+    }
+
+    #[inline]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.vec.reserve_exact(additional)
+    }
+
+    #[inline]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), ThinBoxError> {
+        self.vec.try_reserve(additional)
+    }
+
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.vec.shrink_to_fit()
+    }
+
+    #[inline]
+    pub fn push_str(&mut self, s: &str) {
+        self.vec.extend_from_slice(s.as_bytes())
+    }
+
+    #[inline]
+    pub fn push(&mut self, c: char) {
+        self.push_str(c.encode_utf8(&mut [0; 4]))
+    }
+
+    #[inline]
+    pub fn pop(&mut self) -> Option<char> {
+        let c = self.chars().next_back()?;
+        unsafe { self.vec.set_len(self.len() - c.len_utf8()) };
+        return Some(c);
+    }
+
+    /// Inserts a string slice at the byte position `idx`, which must lie on a `char` boundary.
+    pub fn insert_str(&mut self, idx: usize, s: &str) {
+        assert!(self.is_char_boundary(idx), "insertion index is not a char boundary");
+
+        let len = self.len();
+        self.reserve(s.len());
+        unsafe {
+            let ptr = self.vec.as_mut_ptr().add(idx);
+            core::ptr::copy(ptr, ptr.add(s.len()), len - idx);
+            core::ptr::copy_nonoverlapping(s.as_ptr(), ptr, s.len());
+            self.vec.set_len(len + s.len());
+        }
+    }
+
+    #[inline]
+    pub fn insert(&mut self, idx: usize, c: char) {
+        self.insert_str(idx, c.encode_utf8(&mut [0; 4]))
+    }
+
+    /// Shortens the string to `len` bytes, which must lie on a `char` boundary. Has no effect if `len` is greater than the current length.
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            assert!(self.is_char_boundary(len), "new length is not a char boundary");
+            self.vec.truncate(len)
+        }
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.vec.clear()
+    }
+
+    #[inline]
+    pub fn into_bytes(self) -> ThinVec<u8, A> {
+        self.vec
+    }
+
+    /// Converts the string into a [`ThinBox<str, A>`](ThinBox), handing its allocation over as described in [`ThinVec::into_thin_box`].
+    #[inline]
+    pub fn into_thin_box(self) -> ThinBox<str, A> {
+        unsafe { ThinBox::from_utf8_unchecked(self.vec.into_thin_box()) }
+    }
+}
+
+impl<A: Allocator> Deref for ThinString<A> {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl<A: Allocator> DerefMut for ThinString<A> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut_str()
+    }
+}
+
+impl<A: Allocator> Write for ThinString<A> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.push_str(s);
+        return Ok(());
+    }
+
+    #[inline]
+    fn write_char(&mut self, c: char) -> std::fmt::Result {
+        self.push(c);
+        return Ok(());
+    }
+}
+
+impl<A: Allocator + Clone> Clone for ThinString<A> {
+    #[inline]
+    fn clone(&self) -> Self {
+        return Self {
+            vec: self.vec.clone(),
+        };
+    }
+}
+
+impl Default for ThinString {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Allocator> Debug for ThinString<A> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<A: Allocator> Display for ThinString<A> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<A: Allocator, B: Allocator> PartialEq<ThinString<B>> for ThinString<A> {
+    #[inline]
+    fn eq(&self, other: &ThinString<B>) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<A: Allocator> PartialEq<str> for ThinString<A> {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<A: Allocator> PartialEq<&str> for ThinString<A> {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<A: Allocator> Eq for ThinString<A> {}
+
+impl<A: Allocator, B: Allocator> PartialOrd<ThinString<B>> for ThinString<A> {
+    #[inline]
+    fn partial_cmp(&self, other: &ThinString<B>) -> Option<std::cmp::Ordering> {
+        self.as_str().partial_cmp(other.as_str())
+    }
+}
+
+impl<A: Allocator> Ord for ThinString<A> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<A: Allocator> Hash for ThinString<A> {
+    #[inline]
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        str::hash(self, state)
+    }
+}
+
+impl<A: Allocator> AsRef<str> for ThinString<A> {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl<A: Allocator> AsRef<[u8]> for ThinString<A> {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<A: Allocator> Borrow<str> for ThinString<A> {
+    #[inline]
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl<A: Allocator> BorrowMut<str> for ThinString<A> {
+    #[inline]
+    fn borrow_mut(&mut self) -> &mut str {
+        self
+    }
+}
+
+impl<A: Allocator + Default> From<&str> for ThinString<A> {
+    #[inline]
+    fn from(value: &str) -> Self {
+        Self::from_str_in(value, Default::default())
+    }
+}
+
+impl<A: Allocator + Default> FromStr for ThinString<A> {
+    type Err = Infallible;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+impl<A: Allocator> From<ThinString<A>> for ThinBox<str, A> {
+    #[inline]
+    fn from(value: ThinString<A>) -> Self {
+        value.into_thin_box()
+    }
+}
+
+impl<A: Allocator> Extend<char> for ThinString<A> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        iter.for_each(|c| self.push(c));
+    }
+}
+
+impl<'a, A: Allocator> Extend<&'a str> for ThinString<A> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|s| self.push_str(s));
+    }
+}
+
+impl<A: Allocator + Default> FromIterator<char> for ThinString<A> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        Self::from_iter_in(iter, Default::default())
+    }
+}
+
+impl<'a, A: Allocator + Default> FromIterator<&'a str> for ThinString<A> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        Self::from_iter_in(iter, Default::default())
+    }
+}
@@ -0,0 +1,547 @@
+use crate::{
+    rc::dangling,
+    raw::{deallocate_with_header, header_of, try_allocate_with_header, AllocGuard},
+    CloneThin, ThinBoxError,
+};
+use std::{
+    alloc::{Allocator, Global, Layout},
+    borrow::Borrow,
+    fmt::{Debug, Display},
+    hash::Hash,
+    marker::{PhantomData, Unsize},
+    mem::ManuallyDrop,
+    ops::Deref,
+    ptr::{NonNull, Pointee},
+    sync::atomic::{fence, AtomicUsize, Ordering},
+};
+
+/// Header of a [`ThinArc`] allocation, placed right before the value.
+#[repr(C)]
+pub(crate) struct ArcHeader<M> {
+    pub(crate) strong: AtomicUsize,
+    pub(crate) weak: AtomicUsize,
+    pub(crate) meta: M,
+}
+
+/// Thread-safe reference-counted pointer that is a single pointer wide, even for unsized values.
+/// The reference counts are stored next to the pointee metadata, right before the value.
+pub struct ThinArc<T: ?Sized, A: Allocator = Global> {
+    pub(crate) ptr: NonNull<u8>,
+    alloc: A,
+    _phtm: PhantomData<T>,
+}
+
+impl<T> ThinArc<T> {
+    #[inline]
+    pub fn new(v: T) -> Self {
+        Self::new_in(v, Global)
+    }
+
+    #[inline]
+    pub fn try_new(v: T) -> Result<Self, ThinBoxError> {
+        Self::try_new_in(v, Global)
+    }
+}
+
+impl<T: ?Sized> ThinArc<T> {
+    #[inline]
+    pub fn new_unsize<U: Unsize<T>>(v: U) -> Self {
+        Self::new_unsize_in(v, Global)
+    }
+
+    #[inline]
+    pub fn try_new_unsize<U: Unsize<T>>(v: U) -> Result<Self, ThinBoxError> {
+        Self::try_new_unsize_in(v, Global)
+    }
+
+    #[inline]
+    pub fn into_raw(self) -> NonNull<()> {
+        self.into_raw_with_alloc().0
+    }
+
+    /// # Safety
+    /// `ptr` must have been returned by [`into_raw`](ThinArc::into_raw) for the same `T`, and each call takes over one strong reference.
+    #[inline]
+    pub unsafe fn from_raw(ptr: NonNull<()>) -> Self {
+        return Self::from_raw_with_alloc(ptr, Global);
+    }
+}
+
+impl<T, A: Allocator> ThinArc<T, A> {
+    #[inline]
+    pub fn new_in(v: T, alloc: A) -> Self {
+        Self::try_new_in(v, alloc).expect("error allocating thin value")
+    }
+
+    #[inline]
+    pub fn try_new_in(v: T, alloc: A) -> Result<Self, ThinBoxError> {
+        unsafe { Self::try_new_by_parts_in((), v, alloc) }
+    }
+
+    /// Returns the inner value if this is the only strong reference to it.
+    pub fn try_unwrap(this: Self) -> Result<T, Self> {
+        if this
+            .header()
+            .strong
+            .compare_exchange(1, 0, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+        {
+            return Err(this);
+        }
+        fence(Ordering::Acquire);
+
+        unsafe {
+            let this = ManuallyDrop::new(this);
+            let value = core::ptr::read(this.ptr.as_ptr().cast::<T>());
+            this.release_weak();
+            core::ptr::drop_in_place(&this.alloc as *const A as *mut A);
+            return Ok(value);
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> ThinArc<T, A> {
+    #[inline]
+    pub fn new_unsize_in<U: Unsize<T>>(v: U, alloc: A) -> Self {
+        Self::try_new_unsize_in(v, alloc).expect("error allocating thin value")
+    }
+
+    #[inline]
+    pub fn try_new_unsize_in<U: Unsize<T>>(v: U, alloc: A) -> Result<Self, ThinBoxError> {
+        unsafe { Self::try_new_by_parts_in(core::ptr::metadata(&v as &T), v, alloc) }
+    }
+
+    unsafe fn try_new_by_parts_in<U>(
+        meta: <T as Pointee>::Metadata,
+        v: U,
+        alloc: A,
+    ) -> Result<Self, ThinBoxError> {
+        let ptr = Self::try_allocate_in(meta, Layout::new::<U>(), &alloc)?;
+        ptr.as_ptr().cast::<U>().write(v);
+        return Ok(Self::from_raw_with_alloc(ptr.cast(), alloc));
+    }
+
+    unsafe fn try_allocate_in(
+        meta: <T as Pointee>::Metadata,
+        layout: Layout,
+        alloc: &A,
+    ) -> Result<NonNull<u8>, ThinBoxError> {
+        let header = ArcHeader {
+            strong: AtomicUsize::new(1),
+            weak: AtomicUsize::new(1),
+            meta,
+        };
+        try_allocate_with_header(header, layout, |layout| alloc.allocate(layout))
+    }
+
+    #[inline]
+    pub fn into_raw_with_alloc(self) -> (NonNull<()>, A) {
+        let this = ManuallyDrop::new(self);
+        return unsafe { (this.ptr.cast(), core::ptr::read(&this.alloc)) };
+    }
+
+    /// # Safety
+    /// `ptr` must have been returned by [`into_raw_with_alloc`](ThinArc::into_raw_with_alloc) for the same `T`, `alloc` must be able to release its block,
+    /// and each call takes over one strong reference.
+    #[inline]
+    pub unsafe fn from_raw_with_alloc(ptr: NonNull<()>, alloc: A) -> Self {
+        return Self {
+            ptr: ptr.cast(),
+            alloc,
+            _phtm: PhantomData,
+        };
+    }
+
+    #[inline]
+    pub fn metadata(&self) -> <T as Pointee>::Metadata {
+        self.header().meta
+    }
+
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        return &self.alloc;
+    }
+
+    #[inline]
+    pub fn heap_layout(&self) -> Layout {
+        unsafe {
+            return Layout::new::<ArcHeader<<T as Pointee>::Metadata>>()
+                .extend(self.value_layout())
+                .unwrap_unchecked()
+                .0;
+        }
+    }
+
+    #[inline]
+    pub fn strong_count(this: &Self) -> usize {
+        this.header().strong.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    pub fn weak_count(this: &Self) -> usize {
+        match this.header().weak.load(Ordering::Relaxed) {
+            // the weak count is locked, so there were no other weak references
+            usize::MAX => 0,
+            weak => weak - 1,
+        }
+    }
+
+    /// Creates a new [`ThinArcWeak`] pointer to this allocation.
+    pub fn downgrade(this: &Self) -> ThinArcWeak<T, A>
+    where
+        A: Clone,
+    {
+        let weak = &this.header().weak;
+        let mut current = weak.load(Ordering::Relaxed);
+        loop {
+            // the weak count is locked by `is_unique`
+            if current == usize::MAX {
+                core::hint::spin_loop();
+                current = weak.load(Ordering::Relaxed);
+                continue;
+            } else if current > isize::MAX as usize {
+                std::process::abort();
+            }
+
+            match weak.compare_exchange_weak(current, current + 1, Ordering::Acquire, Ordering::Relaxed) {
+                Ok(_) => {
+                    return ThinArcWeak {
+                        ptr: this.ptr,
+                        alloc: this.alloc.clone(),
+                        _phtm: PhantomData,
+                    }
+                }
+                Err(x) => current = x,
+            }
+        }
+    }
+
+    /// Returns `true` if both pointers point to the same allocation.
+    #[inline]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        this.ptr == other.ptr
+    }
+
+    /// Returns a mutable reference to the value if there are no other references to it.
+    #[inline]
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        if this.is_unique() {
+            return Some(unsafe { &mut *this.value_ptr() });
+        }
+        return None;
+    }
+
+    /// Returns a mutable reference to the value, cloning it into a new allocation if there are other references to it.
+    pub fn make_mut(this: &mut Self) -> &mut T
+    where
+        T: CloneThin,
+        A: Clone,
+    {
+        unsafe {
+            let layout = this.value_layout();
+            let header = this.header();
+
+            if header
+                .strong
+                .compare_exchange(1, 0, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+            {
+                let ptr = Self::try_allocate_in(this.metadata(), layout, &this.alloc)
+                    .expect("error allocating thin value");
+
+                let guard = AllocGuard::<ArcHeader<<T as Pointee>::Metadata>, A>::new(ptr, layout, &this.alloc);
+                T::clone_into_raw(this, ptr.as_ptr());
+                guard.forget();
+
+                *this = Self::from_raw_with_alloc(ptr.cast(), this.alloc.clone());
+            } else if header.weak.load(Ordering::Relaxed) != 1 {
+                // Only weak references are left, so the value can be moved out of their allocation.
+                let ptr = Self::try_allocate_in(this.metadata(), layout, &this.alloc)
+                    .expect("error allocating thin value");
+
+                core::ptr::copy_nonoverlapping(this.ptr.as_ptr(), ptr.as_ptr(), layout.size());
+                this.release_weak();
+                this.ptr = ptr;
+            } else {
+                header.strong.store(1, Ordering::Release);
+            }
+
+            return &mut *this.value_ptr();
+        }
+    }
+
+    /// Checks that there are no other strong or weak references, locking the weak count while the strong count is read
+    /// so that no weak reference can be upgraded in between.
+    fn is_unique(&self) -> bool {
+        let header = self.header();
+        if header
+            .weak
+            .compare_exchange(1, usize::MAX, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+        {
+            let unique = header.strong.load(Ordering::Acquire) == 1;
+            header.weak.store(1, Ordering::Release);
+            return unique;
+        }
+        return false;
+    }
+
+    #[inline]
+    pub(crate) fn header(&self) -> &ArcHeader<<T as Pointee>::Metadata> {
+        unsafe { header_of(self.ptr).as_ref() }
+    }
+
+    #[inline]
+    fn value_ptr(&self) -> *mut T {
+        core::ptr::from_raw_parts_mut(self.ptr.as_ptr(), self.metadata())
+    }
+
+    #[inline]
+    fn value_layout(&self) -> Layout {
+        unsafe { Layout::for_value_raw(self.value_ptr()) }
+    }
+
+    #[inline]
+    unsafe fn release_weak(&self) {
+        release_weak::<T, A>(self.ptr, &self.alloc)
+    }
+}
+
+impl<T: ?Sized, A: Allocator + Clone> Clone for ThinArc<T, A> {
+    #[inline]
+    fn clone(&self) -> Self {
+        if self.header().strong.fetch_add(1, Ordering::Relaxed) > isize::MAX as usize {
+            std::process::abort();
+        }
+        return unsafe { Self::from_raw_with_alloc(self.ptr.cast(), self.alloc.clone()) };
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Drop for ThinArc<T, A> {
+    #[inline]
+    fn drop(&mut self) {
+        if self.header().strong.fetch_sub(1, Ordering::Release) != 1 {
+            return;
+        }
+
+        fence(Ordering::Acquire);
+        unsafe {
+            core::ptr::drop_in_place(self.value_ptr());
+            self.release_weak();
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Deref for ThinArc<T, A> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.value_ptr() }
+    }
+}
+
+impl<T: ?Sized + Debug, A: Allocator> Debug for ThinArc<T, A> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        T::fmt(self, f)
+    }
+}
+
+impl<T: ?Sized + Display, A: Allocator> Display for ThinArc<T, A> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        T::fmt(self, f)
+    }
+}
+
+impl<T: ?Sized + PartialEq, A: Allocator, B: Allocator> PartialEq<ThinArc<T, B>> for ThinArc<T, A> {
+    #[inline]
+    fn eq(&self, other: &ThinArc<T, B>) -> bool {
+        T::eq(self, other)
+    }
+}
+
+impl<T: ?Sized + Eq, A: Allocator> Eq for ThinArc<T, A> {}
+
+impl<T: ?Sized + PartialOrd, A: Allocator, B: Allocator> PartialOrd<ThinArc<T, B>> for ThinArc<T, A> {
+    #[inline]
+    fn partial_cmp(&self, other: &ThinArc<T, B>) -> Option<std::cmp::Ordering> {
+        T::partial_cmp(self, other)
+    }
+}
+
+impl<T: ?Sized + Ord, A: Allocator> Ord for ThinArc<T, A> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        T::cmp(self, other)
+    }
+}
+
+impl<T: ?Sized + Hash, A: Allocator> Hash for ThinArc<T, A> {
+    #[inline]
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        T::hash(self, state)
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Borrow<T> for ThinArc<T, A> {
+    #[inline]
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized, A: Allocator> AsRef<T> for ThinArc<T, A> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+unsafe impl<T: ?Sized + Send + Sync, A: Allocator + Send> Send for ThinArc<T, A> {}
+unsafe impl<T: ?Sized + Send + Sync, A: Allocator + Sync> Sync for ThinArc<T, A> {}
+impl<T: ?Sized, A: Allocator> Unpin for ThinArc<T, A> {}
+
+/// Non-owning, single pointer wide reference to the contents of a [`ThinArc`].
+/// The value is dropped with the last [`ThinArc`], but the header stays allocated until the last `ThinArcWeak` is dropped.
+pub struct ThinArcWeak<T: ?Sized, A: Allocator = Global> {
+    ptr: NonNull<u8>,
+    alloc: A,
+    _phtm: PhantomData<T>,
+}
+
+impl<T: ?Sized> ThinArcWeak<T> {
+    /// Creates a dangling weak pointer that never upgrades. This doesn't allocate.
+    #[inline]
+    pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+}
+
+impl<T: ?Sized, A: Allocator> ThinArcWeak<T, A> {
+    /// Creates a dangling weak pointer that never upgrades. This doesn't allocate.
+    #[inline]
+    pub fn new_in(alloc: A) -> Self {
+        return Self {
+            ptr: dangling(),
+            alloc,
+            _phtm: PhantomData,
+        };
+    }
+
+    /// Attempts to get a strong reference to the value, returning `None` if it has already been dropped.
+    pub fn upgrade(&self) -> Option<ThinArc<T, A>>
+    where
+        A: Clone,
+    {
+        let header = self.header()?;
+        header
+            .strong
+            .fetch_update(Ordering::Acquire, Ordering::Relaxed, |strong| {
+                if strong > isize::MAX as usize {
+                    std::process::abort();
+                }
+                (strong != 0).then_some(strong + 1)
+            })
+            .ok()?;
+
+        return Some(unsafe { ThinArc::from_raw_with_alloc(self.ptr.cast(), self.alloc.clone()) });
+    }
+
+    #[inline]
+    pub fn strong_count(&self) -> usize {
+        self.header()
+            .map_or(0, |header| header.strong.load(Ordering::Relaxed))
+    }
+
+    /// Returns the number of weak references to the allocation, or zero if there are no strong references left.
+    #[inline]
+    pub fn weak_count(&self) -> usize {
+        let Some(header) = self.header() else {
+            return 0;
+        };
+
+        let weak = header.weak.load(Ordering::Acquire);
+        if header.strong.load(Ordering::Relaxed) == 0 {
+            return 0;
+        }
+        return weak - 1;
+    }
+
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        return &self.alloc;
+    }
+
+    /// Returns `true` if both pointers point to the same allocation, or if both are dangling.
+    #[inline]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.ptr == other.ptr
+    }
+
+    #[inline]
+    fn header(&self) -> Option<&ArcHeader<<T as Pointee>::Metadata>> {
+        if self.ptr == dangling() {
+            return None;
+        }
+        return Some(unsafe { header_of(self.ptr).as_ref() });
+    }
+}
+
+impl<T: ?Sized, A: Allocator + Clone> Clone for ThinArcWeak<T, A> {
+    #[inline]
+    fn clone(&self) -> Self {
+        if let Some(header) = self.header() {
+            if header.weak.fetch_add(1, Ordering::Relaxed) > isize::MAX as usize {
+                std::process::abort();
+            }
+        }
+
+        return Self {
+            ptr: self.ptr,
+            alloc: self.alloc.clone(),
+            _phtm: PhantomData,
+        };
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Drop for ThinArcWeak<T, A> {
+    #[inline]
+    fn drop(&mut self) {
+        if self.ptr != dangling() {
+            unsafe { release_weak::<T, A>(self.ptr, &self.alloc) }
+        }
+    }
+}
+
+impl<T: ?Sized> Default for ThinArcWeak<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Debug for ThinArcWeak<T, A> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("(ThinArcWeak)")
+    }
+}
+
+unsafe impl<T: ?Sized + Send + Sync, A: Allocator + Send> Send for ThinArcWeak<T, A> {}
+unsafe impl<T: ?Sized + Send + Sync, A: Allocator + Sync> Sync for ThinArcWeak<T, A> {}
+
+/// Decrements the weak count, releasing the allocation if it reaches zero. The value must have already been dropped.
+unsafe fn release_weak<T: ?Sized, A: Allocator>(ptr: NonNull<u8>, alloc: &A) {
+    let header = header_of::<ArcHeader<<T as Pointee>::Metadata>>(ptr).as_ref();
+    if header.weak.fetch_sub(1, Ordering::Release) == 1 {
+        fence(Ordering::Acquire);
+        let value = core::ptr::from_raw_parts_mut::<T>(ptr.as_ptr(), header.meta);
+        deallocate_with_header::<ArcHeader<<T as Pointee>::Metadata>, A>(
+            ptr,
+            Layout::for_value_raw(value),
+            alloc,
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{CloneThin, ThinBox};
+use std::{
+    alloc::{Allocator, Global},
+    fmt::{Debug, Display},
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut},
+    ptr::{NonNull, Pointee},
+};
+
+/// [`ThinBox`] that keeps a `BITS`-bit tag in the low bits of its pointer, which are always zero because of the value's alignment.
+///
+/// For unsized contents, the pointer is aligned to at least the metadata's alignment (usually the pointer width), which is checked at compile time by [`new`](TaggedThinBox::new).
+/// For sized contents there is no metadata, so [`new_sized`](TaggedThinBox::new_sized) checks the value's alignment instead.
+pub struct TaggedThinBox<T: ?Sized, const BITS: u32, A: Allocator = Global> {
+    ptr: NonNull<u8>,
+    alloc: A,
+    _phtm: PhantomData<NonNull<T>>,
+}
+
+impl<T: ?Sized, const BITS: u32, A: Allocator> TaggedThinBox<T, BITS, A> {
+    /// Mask of the bits used by the tag.
+    pub const MASK: usize = (1 << BITS) - 1;
+
+    const METADATA_ALIGNED: () = assert!(
+        core::mem::align_of::<<T as Pointee>::Metadata>() > Self::MASK,
+        "the metadata's alignment doesn't leave enough free bits for the tag"
+    );
+
+    /// Tags the box, panicking if `tag` doesn't fit in `BITS` bits.
+    /// Fails to compile if the metadata's alignment doesn't leave `BITS` free bits.
+    #[inline]
+    pub fn new(v: ThinBox<T, A>, tag: usize) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let _ = Self::METADATA_ALIGNED;
+        return unsafe { Self::new_unchecked(v, tag) };
+    }
+
+    #[inline]
+    unsafe fn new_unchecked(v: ThinBox<T, A>, tag: usize) -> Self {
+        let (ptr, alloc) = v.into_raw_with_alloc();
+        debug_assert_eq!(ptr.as_ptr() as usize & Self::MASK, 0);
+
+        let mut this = Self {
+            ptr: ptr.cast(),
+            alloc,
+            _phtm: PhantomData,
+        };
+        this.set_tag(tag);
+        return this;
+    }
+
+    #[inline]
+    pub fn tag(&self) -> usize {
+        self.ptr.as_ptr() as usize & Self::MASK
+    }
+
+    /// Replaces the tag, panicking if it doesn't fit in `BITS` bits.
+    #[inline]
+    pub fn set_tag(&mut self, tag: usize) {
+        assert!(tag <= Self::MASK, "tag doesn't fit in {BITS} bits");
+        self.ptr = self.untagged().map_addr(|addr| addr | tag);
+    }
+
+    #[inline]
+    pub fn with_tag(mut self, tag: usize) -> Self {
+        self.set_tag(tag);
+        return self;
+    }
+
+    /// Removes the tag, returning the box and the tag.
+    #[inline]
+    pub fn into_inner(self) -> (ThinBox<T, A>, usize) {
+        let this = ManuallyDrop::new(self);
+        unsafe {
+            let inner = ThinBox::from_raw_with_alloc(this.untagged().cast(), core::ptr::read(&this.alloc));
+            return (inner, this.tag());
+        }
+    }
+
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        return &self.alloc;
+    }
+
+    #[inline]
+    fn untagged(&self) -> NonNull<u8> {
+        self.ptr.map_addr(|addr| unsafe { core::num::NonZeroUsize::new_unchecked(addr.get() & !Self::MASK) })
+    }
+}
+
+impl<T, const BITS: u32, A: Allocator> TaggedThinBox<T, BITS, A> {
+    const VALUE_ALIGNED: () = assert!(
+        core::mem::align_of::<T>() > Self::MASK,
+        "the value's alignment doesn't leave enough free bits for the tag"
+    );
+
+    /// Tags a box with sized contents, panicking if `tag` doesn't fit in `BITS` bits.
+    /// Fails to compile if the value's alignment doesn't leave `BITS` free bits.
+    #[inline]
+    pub fn new_sized(v: ThinBox<T, A>, tag: usize) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let _ = Self::VALUE_ALIGNED;
+        return unsafe { Self::new_unchecked(v, tag) };
+    }
+}
+
+impl<T: ?Sized, const BITS: u32, A: Allocator> Deref for TaggedThinBox<T, BITS, A> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { ThinBox::<T, A>::ref_from_raw(self.untagged().cast()) }
+    }
+}
+
+impl<T: ?Sized, const BITS: u32, A: Allocator> DerefMut for TaggedThinBox<T, BITS, A> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { ThinBox::<T, A>::ref_from_raw(self.untagged().cast()) }
+    }
+}
+
+impl<T: ?Sized, const BITS: u32, A: Allocator> Drop for TaggedThinBox<T, BITS, A> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { drop(ThinBox::<T, &A>::from_raw_with_alloc(self.untagged().cast(), &self.alloc)) }
+    }
+}
+
+impl<T: ?Sized + CloneThin, const BITS: u32, A: Allocator + Clone> Clone for TaggedThinBox<T, BITS, A> {
+    #[inline]
+    fn clone(&self) -> Self {
+        unsafe {
+            let inner = ManuallyDrop::new(ThinBox::<T, &A>::from_raw_with_alloc(self.untagged().cast(), &self.alloc));
+            return Self::new_unchecked(inner.clone_in(self.alloc.clone()), self.tag());
+        }
+    }
+}
+
+impl<T: ?Sized + Debug, const BITS: u32, A: Allocator> Debug for TaggedThinBox<T, BITS, A> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaggedThinBox")
+            .field("tag", &self.tag())
+            .field("value", &&**self)
+            .finish()
+    }
+}
+
+impl<T: ?Sized + Display, const BITS: u32, A: Allocator> Display for TaggedThinBox<T, BITS, A> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        T::fmt(self, f)
+    }
+}
+
+unsafe impl<T: ?Sized + Send, const BITS: u32, A: Allocator + Send> Send for TaggedThinBox<T, BITS, A> {}
+unsafe impl<T: ?Sized + Sync, const BITS: u32, A: Allocator + Sync> Sync for TaggedThinBox<T, BITS, A> {}
+impl<T: ?Sized, const BITS: u32, A: Allocator> Unpin for TaggedThinBox<T, BITS, A> {}
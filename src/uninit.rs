@@ -0,0 +1,155 @@
+use crate::{ThinBox, ThinBoxError};
+use std::{
+    alloc::{Allocator, Global, Layout},
+    mem::MaybeUninit,
+};
+
+impl<T> ThinBox<T> {
+    #[inline]
+    pub fn new_uninit() -> ThinBox<MaybeUninit<T>> {
+        Self::new_uninit_in(Global)
+    }
+
+    #[inline]
+    pub fn try_new_uninit() -> Result<ThinBox<MaybeUninit<T>>, ThinBoxError> {
+        Self::try_new_uninit_in(Global)
+    }
+
+    #[inline]
+    pub fn new_zeroed() -> ThinBox<MaybeUninit<T>> {
+        Self::new_zeroed_in(Global)
+    }
+
+    #[inline]
+    pub fn try_new_zeroed() -> Result<ThinBox<MaybeUninit<T>>, ThinBoxError> {
+        Self::try_new_zeroed_in(Global)
+    }
+}
+
+impl<T, A: Allocator> ThinBox<T, A> {
+    #[inline]
+    pub fn new_uninit_in(alloc: A) -> ThinBox<MaybeUninit<T>, A> {
+        Self::try_new_uninit_in(alloc).expect("error allocating thin value")
+    }
+
+    /// Allocates room for a value on the heap, without initializing it.
+    #[inline]
+    pub fn try_new_uninit_in(alloc: A) -> Result<ThinBox<MaybeUninit<T>, A>, ThinBoxError> {
+        unsafe {
+            let ptr = ThinBox::<MaybeUninit<T>, A>::try_allocate_in((), Layout::new::<T>(), &alloc)?;
+            return Ok(ThinBox::from_raw_with_alloc(ptr.cast(), alloc));
+        }
+    }
+
+    #[inline]
+    pub fn new_zeroed_in(alloc: A) -> ThinBox<MaybeUninit<T>, A> {
+        Self::try_new_zeroed_in(alloc).expect("error allocating thin value")
+    }
+
+    /// Allocates room for a value on the heap, filling its memory with zeroes.
+    #[inline]
+    pub fn try_new_zeroed_in(alloc: A) -> Result<ThinBox<MaybeUninit<T>, A>, ThinBoxError> {
+        unsafe {
+            let ptr = ThinBox::<MaybeUninit<T>, A>::try_allocate_zeroed_in(
+                (),
+                Layout::new::<T>(),
+                &alloc,
+            )?;
+            return Ok(ThinBox::from_raw_with_alloc(ptr.cast(), alloc));
+        }
+    }
+}
+
+impl<T> ThinBox<[T]> {
+    #[inline]
+    pub fn new_uninit_slice(len: usize) -> ThinBox<[MaybeUninit<T>]> {
+        Self::new_uninit_slice_in(len, Global)
+    }
+
+    #[inline]
+    pub fn try_new_uninit_slice(len: usize) -> Result<ThinBox<[MaybeUninit<T>]>, ThinBoxError> {
+        Self::try_new_uninit_slice_in(len, Global)
+    }
+
+    #[inline]
+    pub fn new_zeroed_slice(len: usize) -> ThinBox<[MaybeUninit<T>]> {
+        Self::new_zeroed_slice_in(len, Global)
+    }
+
+    #[inline]
+    pub fn try_new_zeroed_slice(len: usize) -> Result<ThinBox<[MaybeUninit<T>]>, ThinBoxError> {
+        Self::try_new_zeroed_slice_in(len, Global)
+    }
+}
+
+impl<T, A: Allocator> ThinBox<[T], A> {
+    #[inline]
+    pub fn new_uninit_slice_in(len: usize, alloc: A) -> ThinBox<[MaybeUninit<T>], A> {
+        Self::try_new_uninit_slice_in(len, alloc).expect("error allocating thin value")
+    }
+
+    /// Allocates room for `len` elements on the heap, without initializing them.
+    #[inline]
+    pub fn try_new_uninit_slice_in(
+        len: usize,
+        alloc: A,
+    ) -> Result<ThinBox<[MaybeUninit<T>], A>, ThinBoxError> {
+        unsafe {
+            let layout = Layout::array::<T>(len).map_err(|_| ThinBoxError::CapacityOverflow)?;
+            let ptr = ThinBox::<[MaybeUninit<T>], A>::try_allocate_in(len, layout, &alloc)?;
+            return Ok(ThinBox::from_raw_with_alloc(ptr.cast(), alloc));
+        }
+    }
+
+    #[inline]
+    pub fn new_zeroed_slice_in(len: usize, alloc: A) -> ThinBox<[MaybeUninit<T>], A> {
+        Self::try_new_zeroed_slice_in(len, alloc).expect("error allocating thin value")
+    }
+
+    /// Allocates room for `len` elements on the heap, filling their memory with zeroes.
+    #[inline]
+    pub fn try_new_zeroed_slice_in(
+        len: usize,
+        alloc: A,
+    ) -> Result<ThinBox<[MaybeUninit<T>], A>, ThinBoxError> {
+        unsafe {
+            let layout = Layout::array::<T>(len).map_err(|_| ThinBoxError::CapacityOverflow)?;
+            let ptr = ThinBox::<[MaybeUninit<T>], A>::try_allocate_zeroed_in(len, layout, &alloc)?;
+            return Ok(ThinBox::from_raw_with_alloc(ptr.cast(), alloc));
+        }
+    }
+}
+
+impl<T, A: Allocator> ThinBox<MaybeUninit<T>, A> {
+    /// Converts to `ThinBox<T, A>`, reusing the allocation.
+    ///
+    /// # Safety
+    /// The value must be initialized.
+    #[inline]
+    pub unsafe fn assume_init(self) -> ThinBox<T, A> {
+        let (ptr, alloc) = self.into_raw_with_alloc();
+        return ThinBox::from_raw_with_alloc(ptr, alloc);
+    }
+
+    /// Writes the value and converts to `ThinBox<T, A>`, reusing the allocation.
+    /// This is an associated function so that it doesn't shadow [`MaybeUninit::write`] through `Deref`.
+    #[inline]
+    pub fn write(mut this: Self, value: T) -> ThinBox<T, A> {
+        unsafe {
+            (*this).write(value);
+            return this.assume_init();
+        }
+    }
+}
+
+impl<T, A: Allocator> ThinBox<[MaybeUninit<T>], A> {
+    /// Converts to `ThinBox<[T], A>`, reusing the allocation.
+    ///
+    /// # Safety
+    /// Every element of the slice must be initialized.
+    #[inline]
+    pub unsafe fn assume_init(self) -> ThinBox<[T], A> {
+        let (ptr, alloc) = self.into_raw_with_alloc();
+        return ThinBox::from_raw_with_alloc(ptr, alloc);
+    }
+}
@@ -0,0 +1,712 @@
+use crate::{raw::header_of, ThinBox, ThinBoxError};
+use std::{
+    alloc::{Allocator, Global, Layout},
+    borrow::{Borrow, BorrowMut},
+    fmt::Debug,
+    hash::Hash,
+    iter::FusedIterator,
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ops::{Bound, Deref, DerefMut, RangeBounds},
+    ptr::NonNull,
+};
+
+/// Growable vector that is a single pointer wide. Its capacity and length are stored in the heap, around the elements.
+/// An empty vector without capacity doesn't allocate.
+///
+/// The block is laid out as a [`ThinBox<[T]>`](ThinBox) of `capacity` elements (with the capacity as its metadata) followed by the length,
+/// so that it can be handed over to a box by just dropping the length and the spare capacity.
+pub struct ThinVec<T, A: Allocator = Global> {
+    ptr: NonNull<T>,
+    alloc: A,
+    _phtm: PhantomData<T>,
+}
+
+impl<T> ThinVec<T> {
+    #[inline]
+    pub const fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    #[inline]
+    pub fn with_capacity(cap: usize) -> Self {
+        Self::with_capacity_in(cap, Global)
+    }
+}
+
+impl<T, A: Allocator> ThinVec<T, A> {
+    #[inline]
+    pub const fn new_in(alloc: A) -> Self {
+        return Self {
+            ptr: NonNull::dangling(),
+            alloc,
+            _phtm: PhantomData,
+        };
+    }
+
+    #[inline]
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        let mut this = Self::new_in(alloc);
+        this.reserve_exact(cap);
+        return this;
+    }
+
+    #[inline]
+    pub fn from_iter_in<I: IntoIterator<Item = T>>(iter: I, alloc: A) -> Self {
+        let mut this = Self::new_in(alloc);
+        this.extend(iter);
+        return this;
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        match self.is_dangling() {
+            true => 0,
+            false => unsafe { *self.len_ptr().as_ptr() },
+        }
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        match self.is_dangling() {
+            true => 0,
+            false => unsafe { *header_of::<usize>(self.ptr.cast()).as_ptr() },
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { core::slice::from_raw_parts(self.ptr.as_ptr(), self.len()) }
+    }
+
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { core::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len()) }
+    }
+
+    #[inline]
+    pub fn as_ptr(&self) -> *const T {
+        return self.ptr.as_ptr();
+    }
+
+    #[inline]
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        return self.ptr.as_ptr();
+    }
+
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        return &self.alloc;
+    }
+
+    /// Forces the length of the vector to `len`.
+    ///
+    /// # Safety
+    /// `len` must be less than or equal to [`capacity`](Self::capacity), and the elements up to `len` must be initialized.
+    #[inline]
+    pub unsafe fn set_len(&mut self, len: usize) {
+        debug_assert!(len <= self.capacity());
+        if !self.is_dangling() {
+            self.len_ptr().as_ptr().write(len);
+        }
+    }
+
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.try_reserve(additional)
+            .expect("error allocating thin value")
+    }
+
+    #[inline]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.try_reserve_exact(additional)
+            .expect("error allocating thin value")
+    }
+
+    /// Reserves capacity for at least `additional` more elements, growing the capacity geometrically.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), ThinBoxError> {
+        let cap = self.capacity();
+        let required = self
+            .len()
+            .checked_add(additional)
+            .ok_or(ThinBoxError::CapacityOverflow)?;
+
+        if required <= cap {
+            return Ok(());
+        }
+        return self.try_resize(required.max(cap.saturating_mul(2)).max(4));
+    }
+
+    /// Reserves capacity for exactly `additional` more elements.
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), ThinBoxError> {
+        let required = self
+            .len()
+            .checked_add(additional)
+            .ok_or(ThinBoxError::CapacityOverflow)?;
+
+        if required <= self.capacity() {
+            return Ok(());
+        }
+        return self.try_resize(required);
+    }
+
+    /// Shrinks the capacity to the length of the vector, releasing the allocation if it's empty.
+    pub fn shrink_to_fit(&mut self) {
+        let len = self.len();
+        if len == self.capacity() {
+            return;
+        }
+
+        if len == 0 {
+            unsafe { self.deallocate() };
+            self.ptr = NonNull::dangling();
+            return;
+        }
+
+        self.try_resize(len).expect("error allocating thin value")
+    }
+
+    #[inline]
+    pub fn push(&mut self, v: T) {
+        let len = self.len();
+        if len == self.capacity() {
+            self.reserve(1);
+        }
+
+        unsafe {
+            self.ptr.as_ptr().add(len).write(v);
+            self.set_len(len + 1);
+        }
+    }
+
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        let len = self.len().checked_sub(1)?;
+        unsafe {
+            self.set_len(len);
+            return Some(self.ptr.as_ptr().add(len).read());
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, v: T) {
+        let len = self.len();
+        assert!(
+            index <= len,
+            "insertion index (is {index}) should be <= len (is {len})"
+        );
+
+        if len == self.capacity() {
+            self.reserve(1);
+        }
+
+        unsafe {
+            let ptr = self.ptr.as_ptr().add(index);
+            core::ptr::copy(ptr, ptr.add(1), len - index);
+            ptr.write(v);
+            self.set_len(len + 1);
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len();
+        assert!(
+            index < len,
+            "removal index (is {index}) should be < len (is {len})"
+        );
+
+        unsafe {
+            let ptr = self.ptr.as_ptr().add(index);
+            let v = ptr.read();
+            core::ptr::copy(ptr.add(1), ptr, len - index - 1);
+            self.set_len(len - 1);
+            return v;
+        }
+    }
+
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        let len = self.len();
+        assert!(
+            index < len,
+            "swap_remove index (is {index}) should be < len (is {len})"
+        );
+
+        unsafe {
+            let ptr = self.ptr.as_ptr();
+            let v = ptr.add(index).read();
+            core::ptr::copy(ptr.add(len - 1), ptr.add(index), 1);
+            self.set_len(len - 1);
+            return v;
+        }
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        let old_len = self.len();
+        if len >= old_len {
+            return;
+        }
+
+        unsafe {
+            self.set_len(len);
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                self.ptr.as_ptr().add(len),
+                old_len - len,
+            ))
+        }
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.truncate(0)
+    }
+
+    #[inline]
+    pub fn extend_from_slice(&mut self, other: &[T])
+    where
+        T: Clone,
+    {
+        self.extend(other.iter().cloned())
+    }
+
+    #[inline]
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        self.retain_mut(|x| f(x))
+    }
+
+    /// Keeps only the elements for which `f` returns `true`, preserving their order.
+    pub fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, mut f: F) {
+        struct Guard<'a, T, A: Allocator> {
+            vec: &'a mut ThinVec<T, A>,
+            len: usize,
+            processed: usize,
+            deleted: usize,
+        }
+
+        impl<T, A: Allocator> Drop for Guard<'_, T, A> {
+            #[inline]
+            fn drop(&mut self) {
+                unsafe {
+                    // shift the unprocessed elements back if `f` panicked
+                    if self.deleted > 0 {
+                        let ptr = self.vec.ptr.as_ptr().add(self.processed);
+                        core::ptr::copy(ptr, ptr.sub(self.deleted), self.len - self.processed);
+                    }
+                    self.vec.set_len(self.len - self.deleted);
+                }
+            }
+        }
+
+        let len = self.len();
+        unsafe { self.set_len(0) };
+
+        let mut guard = Guard {
+            vec: self,
+            len,
+            processed: 0,
+            deleted: 0,
+        };
+
+        while guard.processed < len {
+            unsafe {
+                let ptr = guard.vec.ptr.as_ptr().add(guard.processed);
+                if !f(&mut *ptr) {
+                    guard.processed += 1;
+                    guard.deleted += 1;
+                    core::ptr::drop_in_place(ptr);
+                    continue;
+                }
+
+                if guard.deleted > 0 {
+                    core::ptr::copy_nonoverlapping(ptr, ptr.sub(guard.deleted), 1);
+                }
+                guard.processed += 1;
+            }
+        }
+    }
+
+    /// Removes the elements in `range`, returning them as an iterator.
+    /// The elements that aren't consumed are dropped along with the iterator.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T, A> {
+        let len = self.len();
+        let start = match range.start_bound() {
+            Bound::Included(&x) => x,
+            Bound::Excluded(&x) => x.checked_add(1).expect("attempted to drain from an overflowing index"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&x) => x.checked_add(1).expect("attempted to drain up to an overflowing index"),
+            Bound::Excluded(&x) => x,
+            Bound::Unbounded => len,
+        };
+
+        assert!(start <= end, "slice index starts at {start} but ends at {end}");
+        assert!(end <= len, "range end index {end} out of range for slice of length {len}");
+
+        unsafe { self.set_len(start) };
+        return Drain {
+            vec: self,
+            idx: start,
+            end,
+            tail_start: end,
+            tail_len: len - end,
+        };
+    }
+
+    /// Converts the vector into a [`ThinBox<[T], A>`](ThinBox), shrinking it to its length.
+    ///
+    /// The elements aren't copied: the block is shrunk to drop the spare capacity and the length after it,
+    /// and the length is written over the capacity, where the box keeps its metadata.
+    /// Whether the block stays in place when shrunk is up to the allocator.
+    pub fn into_thin_box(self) -> ThinBox<[T], A> {
+        let len = self.len();
+        let this = ManuallyDrop::new(self);
+
+        unsafe {
+            if this.is_dangling() {
+                let alloc = core::ptr::read(&this.alloc);
+                return ThinBox::<[T], A>::from_iter_in(core::iter::empty(), alloc);
+            }
+
+            let (layout, offset) = Self::layout(this.capacity()).unwrap_unchecked();
+            let (box_layout, _) = Self::box_layout(len).unwrap_unchecked();
+
+            let base = this.ptr.cast::<u8>().sub(offset);
+            let base = match this.alloc.shrink(base, layout, box_layout) {
+                Ok(x) => x.cast::<u8>(),
+                Err(_) => {
+                    // the block is left untouched, so the vector is dropped as usual
+                    drop(ManuallyDrop::into_inner(this));
+                    panic!("error allocating thin value: {:?}", ThinBoxError::AllocError { layout: box_layout })
+                }
+            };
+
+            let alloc = core::ptr::read(&this.alloc);
+            let ptr = base.add(offset);
+            header_of::<usize>(ptr).write(len);
+            return ThinBox::from_raw_with_alloc(ptr.cast(), alloc);
+        }
+    }
+
+    /// Layout of a [`ThinBox<[T]>`](ThinBox) of `len` elements, and the offset of the first element.
+    #[inline]
+    fn box_layout(len: usize) -> Result<(Layout, usize), ThinBoxError> {
+        let metadata = Layout::new::<usize>();
+        let value = Layout::array::<T>(len).map_err(|_| ThinBoxError::CapacityOverflow)?;
+        return metadata
+            .extend(value)
+            .map_err(|_| ThinBoxError::LayoutOverflow { metadata, value });
+    }
+
+    /// Layout of the block for `cap` elements followed by the length, and the offset of the first element.
+    #[inline]
+    fn layout(cap: usize) -> Result<(Layout, usize), ThinBoxError> {
+        let (layout, offset) = Self::box_layout(cap)?;
+        let (layout, _) = layout
+            .extend(Layout::new::<usize>())
+            .map_err(|_| ThinBoxError::CapacityOverflow)?;
+        return Ok((layout, offset));
+    }
+
+    /// Reallocates the block to fit exactly `cap` elements, which must not be less than the length.
+    fn try_resize(&mut self, cap: usize) -> Result<(), ThinBoxError> {
+        let (layout, offset) = Self::layout(cap)?;
+        let old_cap = self.capacity();
+        let len = self.len();
+
+        unsafe {
+            let base = match self.is_dangling() {
+                true => self.alloc.allocate(layout),
+                false => {
+                    let (old_layout, _) = Self::layout(old_cap).unwrap_unchecked();
+                    let old_base = self.ptr.cast::<u8>().sub(offset);
+                    match cap > old_cap {
+                        true => self.alloc.grow(old_base, old_layout, layout),
+                        false => self.alloc.shrink(old_base, old_layout, layout),
+                    }
+                }
+            };
+
+            let base = base.map_err(|_| ThinBoxError::AllocError { layout })?;
+            let ptr = base.cast::<u8>().add(offset);
+            header_of::<usize>(ptr).write(cap);
+
+            // the length follows the capacity, so it moves whenever the capacity changes
+            self.ptr = ptr.cast();
+            self.len_ptr().as_ptr().write(len);
+            return Ok(());
+        }
+    }
+
+    /// Releases the block without dropping the elements. The vector must not be dangling.
+    #[inline]
+    unsafe fn deallocate(&mut self) {
+        let (layout, offset) = Self::layout(self.capacity()).unwrap_unchecked();
+        self.alloc.deallocate(self.ptr.cast::<u8>().sub(offset), layout);
+    }
+
+    #[inline]
+    fn is_dangling(&self) -> bool {
+        self.ptr == NonNull::dangling()
+    }
+
+    /// Returns a pointer to the length, right after the capacity (rounded up to its alignment). The vector must not be dangling.
+    #[inline]
+    unsafe fn len_ptr(&self) -> NonNull<usize> {
+        let cap = *header_of::<usize>(self.ptr.cast()).as_ptr();
+        let offset = (cap * core::mem::size_of::<T>()).next_multiple_of(core::mem::align_of::<usize>());
+        return self.ptr.cast::<u8>().add(offset).cast();
+    }
+}
+
+impl<T, A: Allocator> Drop for ThinVec<T, A> {
+    #[inline]
+    fn drop(&mut self) {
+        if self.is_dangling() {
+            return;
+        }
+
+        unsafe {
+            core::ptr::drop_in_place(self.as_mut_slice());
+            self.deallocate();
+        }
+    }
+}
+
+impl<T, A: Allocator> Deref for ThinVec<T, A> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl<T, A: Allocator> DerefMut for ThinVec<T, A> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut_slice()
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone> Clone for ThinVec<T, A> {
+    #[inline]
+    fn clone(&self) -> Self {
+        let mut this = Self::with_capacity_in(self.len(), self.alloc.clone());
+        this.extend_from_slice(self);
+        return this;
+    }
+}
+
+impl<T: Debug, A: Allocator> Debug for ThinVec<T, A> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        <[T]>::fmt(self, f)
+    }
+}
+
+impl<T> Default for ThinVec<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialEq, A: Allocator, B: Allocator> PartialEq<ThinVec<T, B>> for ThinVec<T, A> {
+    #[inline]
+    fn eq(&self, other: &ThinVec<T, B>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Eq, A: Allocator> Eq for ThinVec<T, A> {}
+
+impl<T: PartialOrd, A: Allocator, B: Allocator> PartialOrd<ThinVec<T, B>> for ThinVec<T, A> {
+    #[inline]
+    fn partial_cmp(&self, other: &ThinVec<T, B>) -> Option<std::cmp::Ordering> {
+        self.as_slice().partial_cmp(other.as_slice())
+    }
+}
+
+impl<T: Ord, A: Allocator> Ord for ThinVec<T, A> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        <[T]>::cmp(self, other)
+    }
+}
+
+impl<T: Hash, A: Allocator> Hash for ThinVec<T, A> {
+    #[inline]
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        <[T]>::hash(self, state)
+    }
+}
+
+impl<T, A: Allocator> AsRef<[T]> for ThinVec<T, A> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, A: Allocator> AsMut<[T]> for ThinVec<T, A> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T, A: Allocator> Borrow<[T]> for ThinVec<T, A> {
+    #[inline]
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, A: Allocator> BorrowMut<[T]> for ThinVec<T, A> {
+    #[inline]
+    fn borrow_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T, A: Allocator> Extend<T> for ThinVec<T, A> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        iter.for_each(|x| self.push(x));
+    }
+}
+
+impl<'a, T: 'a + Copy, A: Allocator> Extend<&'a T> for ThinVec<T, A> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied())
+    }
+}
+
+impl<T, A: Allocator + Default> FromIterator<T> for ThinVec<T, A> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_iter_in(iter, A::default())
+    }
+}
+
+impl<'a, T, A: Allocator> IntoIterator for &'a ThinVec<T, A> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, A: Allocator> IntoIterator for &'a mut ThinVec<T, A> {
+    type Item = &'a mut T;
+    type IntoIter = core::slice::IterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T: Clone, A: Allocator + Default> From<&[T]> for ThinVec<T, A> {
+    #[inline]
+    fn from(value: &[T]) -> Self {
+        let mut this = Self::with_capacity_in(value.len(), A::default());
+        this.extend_from_slice(value);
+        return this;
+    }
+}
+
+impl<T, A: Allocator> From<ThinVec<T, A>> for ThinBox<[T], A> {
+    #[inline]
+    fn from(value: ThinVec<T, A>) -> Self {
+        value.into_thin_box()
+    }
+}
+
+unsafe impl<T: Send, A: Allocator + Send> Send for ThinVec<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for ThinVec<T, A> {}
+
+/// Draining iterator returned by [`ThinVec::drain`].
+pub struct Drain<'a, T, A: Allocator = Global> {
+    vec: &'a mut ThinVec<T, A>,
+    idx: usize,
+    end: usize,
+    tail_start: usize,
+    tail_len: usize,
+}
+
+impl<T, A: Allocator> Iterator for Drain<'_, T, A> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.idx == self.end {
+            return None;
+        }
+
+        self.idx += 1;
+        return Some(unsafe { self.vec.ptr.as_ptr().add(self.idx - 1).read() });
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.idx;
+        return (len, Some(len));
+    }
+}
+
+impl<T, A: Allocator> DoubleEndedIterator for Drain<'_, T, A> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.idx == self.end {
+            return None;
+        }
+
+        self.end -= 1;
+        return Some(unsafe { self.vec.ptr.as_ptr().add(self.end).read() });
+    }
+}
+
+impl<T, A: Allocator> ExactSizeIterator for Drain<'_, T, A> {}
+impl<T, A: Allocator> FusedIterator for Drain<'_, T, A> {}
+
+impl<T, A: Allocator> Drop for Drain<'_, T, A> {
+    fn drop(&mut self) {
+        /// Moves the tail back to close the gap, even if dropping the remaining elements panics.
+        struct MoveTail<'r, 'a, T, A: Allocator>(&'r mut Drain<'a, T, A>);
+
+        impl<T, A: Allocator> Drop for MoveTail<'_, '_, T, A> {
+            #[inline]
+            fn drop(&mut self) {
+                unsafe {
+                    let vec = &mut *self.0.vec;
+                    let start = vec.len();
+                    if self.0.tail_len > 0 && self.0.tail_start != start {
+                        let ptr = vec.ptr.as_ptr();
+                        core::ptr::copy(ptr.add(self.0.tail_start), ptr.add(start), self.0.tail_len);
+                    }
+                    vec.set_len(start + self.0.tail_len);
+                }
+            }
+        }
+
+        let guard = MoveTail(self);
+        let drain = &mut *guard.0;
+        let remaining = core::ptr::slice_from_raw_parts_mut(
+            unsafe { drain.vec.ptr.as_ptr().add(drain.idx) },
+            drain.end - drain.idx,
+        );
+
+        drain.idx = drain.end;
+        unsafe { core::ptr::drop_in_place(remaining) }
+    }
+}
@@ -0,0 +1,64 @@
+#![cfg(feature = "rkyv")]
+
+use rkyv::{rancor::Error, Archive, Archived, Deserialize, Serialize};
+use thinnbox::{ArchivedThinBox, ThinBox};
+
+#[derive(Debug, PartialEq, Archive, Serialize, Deserialize)]
+struct Dataset {
+    name: ThinBox<str>,
+    values: ThinBox<[u32]>,
+    scale: ThinBox<f64>,
+    rows: ThinBox<[ThinBox<str>]>,
+}
+
+fn dataset () -> Dataset {
+    Dataset {
+        name: ThinBox::from("dataset"),
+        values: ThinBox::from_slice(&[1, 2, 3]),
+        scale: ThinBox::new(0.5),
+        rows: ThinBox::from_vec(vec![ThinBox::from("a"), ThinBox::from("bc")]),
+    }
+}
+
+#[test]
+fn access () {
+    let bytes = rkyv::to_bytes::<Error>(&dataset()).unwrap();
+    let archived = rkyv::access::<ArchivedDataset, Error>(&bytes).unwrap();
+
+    assert_eq!(&*archived.name, "dataset");
+    assert_eq!(archived.values.iter().map(|x| x.to_native()).collect::<Vec<_>>(), [1, 2, 3]);
+    assert_eq!(archived.scale.to_native(), 0.5);
+    assert_eq!(archived.rows.iter().map(|x| &**x).collect::<Vec<_>>(), ["a", "bc"]);
+    assert!(archived.name == ThinBox::<str>::from("dataset"));
+}
+
+#[test]
+fn round_trip () {
+    let bytes = rkyv::to_bytes::<Error>(&dataset()).unwrap();
+    let v = rkyv::from_bytes::<Dataset, Error>(&bytes).unwrap();
+    assert_eq!(v, dataset());
+}
+
+#[test]
+fn thin () {
+    let bytes = rkyv::to_bytes::<Error>(&dataset()).unwrap();
+    let archived = rkyv::access::<ArchivedDataset, Error>(&bytes).unwrap();
+    assert_eq!(core::mem::size_of_val(&archived.name), core::mem::size_of::<rkyv::RawRelPtr>());
+    assert_eq!(core::mem::size_of::<ArchivedDataset>(), 4 * core::mem::size_of::<rkyv::RawRelPtr>());
+}
+
+#[test]
+fn box_compatible () {
+    let bytes = rkyv::to_bytes::<Error>(&ThinBox::<[u16]>::from_slice(&[4, 5])).unwrap();
+    let archived = rkyv::access::<ArchivedThinBox<[Archived<u16>]>, Error>(&bytes).unwrap();
+    let v = rkyv::deserialize::<Box<[u16]>, Error>(archived.as_archived_box()).unwrap();
+    assert_eq!(&*v, &[4, 5]);
+}
+
+#[test]
+fn invalid () {
+    let mut bytes = rkyv::to_bytes::<Error>(&ThinBox::<[u16]>::from_slice(&[4, 5])).unwrap();
+    let len = bytes.len();
+    bytes[len - 4..].copy_from_slice(&1000i32.to_le_bytes());
+    assert!(rkyv::access::<ArchivedThinBox<[Archived<u16>]>, Error>(&bytes).is_err());
+}
@@ -0,0 +1,120 @@
+#![feature(allocator_api)]
+
+use std::{
+    alloc::{AllocError, Allocator, Global, Layout},
+    cell::Cell,
+    fmt::Display,
+    ptr::NonNull,
+    rc::Rc,
+    sync::{atomic::Ordering, Arc},
+    thread,
+};
+use thinnbox::{AtomicOptionThinBox, AtomicThinBox, ThinBox};
+
+/// Allocator that counts the live blocks.
+#[derive(Default)]
+struct Counting(Cell<usize>);
+
+unsafe impl Allocator for &Counting {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.set(self.0.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.0.set(self.0.get() - 1);
+        Global.deallocate(ptr, layout)
+    }
+}
+
+#[test]
+fn swap () {
+    let a = AtomicThinBox::new(ThinBox::<dyn Display + Send + Sync>::new_unsize(1));
+    assert_eq!(core::mem::size_of_val(&a), core::mem::size_of::<usize>());
+
+    let prev = a.swap(ThinBox::new_unsize("two"), Ordering::AcqRel);
+    assert_eq!(prev.to_string(), "1");
+    a.store(ThinBox::new_unsize(3.5), Ordering::Release);
+    assert_eq!(a.into_inner().to_string(), "3.5");
+}
+
+#[test]
+fn compare_exchange () {
+    let mut a = AtomicThinBox::new(ThinBox::new(1));
+    let current = a.load_raw(Ordering::Acquire);
+
+    let prev = a.compare_exchange(current, ThinBox::new(2), Ordering::AcqRel, Ordering::Acquire).unwrap();
+    assert_eq!(*prev, 1);
+
+    let rejected = a.compare_exchange(current, ThinBox::new(3), Ordering::AcqRel, Ordering::Acquire).unwrap_err();
+    assert_eq!(*rejected, 3);
+
+    *a.get_mut() += 10;
+    assert_eq!(*a.into_inner(), 12);
+}
+
+#[test]
+fn option () {
+    let rc = Rc::new(());
+    let mut a = AtomicOptionThinBox::<Rc<()>>::default();
+    assert!(a.take(Ordering::Acquire).is_none());
+
+    a.store(Some(ThinBox::new(rc.clone())), Ordering::Release);
+    assert!(a.get_mut().is_some());
+    assert!(a.compare_exchange(None, None, Ordering::AcqRel, Ordering::Acquire).is_err());
+
+    let current = a.load_raw(Ordering::Acquire);
+    assert!(a.compare_exchange(current, None, Ordering::AcqRel, Ordering::Acquire).unwrap().is_some());
+    assert_eq!(Rc::strong_count(&rc), 1);
+
+    a.store(Some(ThinBox::new(rc.clone())), Ordering::Release);
+    drop(a);
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
+
+#[test]
+fn threads () {
+    let a = Arc::new(AtomicOptionThinBox::new(Some(ThinBox::<[u64]>::from_slice(&[0]))));
+    let handles = (1..=8u64)
+        .map(|i| {
+            let a = a.clone();
+            thread::spawn(move || a.swap(Some(ThinBox::from_slice(&[i; 4])), Ordering::AcqRel))
+        })
+        .collect::<Vec<_>>();
+
+    let mut seen = handles
+        .into_iter()
+        .map(|x| x.join().unwrap().unwrap()[0])
+        .collect::<Vec<_>>();
+    seen.push(a.take(Ordering::Acquire).unwrap()[0]);
+    seen.sort();
+
+    assert_eq!(seen, (0..=8).collect::<Vec<_>>());
+}
+
+#[test]
+fn custom_allocator () {
+    let alloc = Counting::default();
+    let rc = Rc::new(());
+    let mut a = AtomicThinBox::new(ThinBox::new_in(rc.clone(), &alloc));
+    assert_eq!(Rc::strong_count(a.get_mut()), 2);
+
+    let prev = a.swap(ThinBox::new_in(Rc::new(()), &alloc), Ordering::AcqRel);
+    assert_eq!(Rc::strong_count(&prev), 2);
+    drop(prev);
+    a.store(ThinBox::new_in(rc.clone(), &alloc), Ordering::Release);
+    let current = a.load_raw(Ordering::Acquire);
+    assert!(a.compare_exchange(current, ThinBox::new_in(rc.clone(), &alloc), Ordering::AcqRel, Ordering::Acquire).is_ok());
+    drop(a);
+    assert_eq!(alloc.0.get(), 0);
+
+    let b = AtomicOptionThinBox::<Rc<()>, _>::new_in(&alloc);
+    b.store(Some(ThinBox::new_in(rc.clone(), &alloc)), Ordering::Release);
+    assert_eq!(alloc.0.get(), 1);
+    assert!(b.take(Ordering::Acquire).is_some());
+    assert!(b.compare_exchange(None, Some(ThinBox::new_in(rc.clone(), &alloc)), Ordering::AcqRel, Ordering::Acquire).is_ok());
+    drop(b);
+
+    assert_eq!(alloc.0.get(), 0);
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
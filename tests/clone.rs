@@ -0,0 +1,73 @@
+use std::{
+    cell::Cell,
+    fmt::Debug,
+    ops::Deref,
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::Path,
+};
+use thinnbox::{CloneThin, ThinBox};
+
+trait Shape: Debug + CloneThin {
+    fn area(&self) -> f64;
+}
+
+#[derive(Debug, Clone)]
+struct Square(f64);
+
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.0 * self.0
+    }
+}
+
+#[test]
+fn sized () {
+    let v = ThinBox::new(String::from("hello"));
+    assert_eq!(v.clone(), v);
+}
+
+#[test]
+fn unsized_contents () {
+    let v = ThinBox::<[String]>::from_fn(3, |i| i.to_string());
+    assert_eq!(v.clone().deref(), ["0", "1", "2"]);
+
+    let v = ThinBox::<str>::from("hello");
+    assert_eq!(v.clone().deref(), "hello");
+
+    let v = ThinBox::<Path>::from(Path::new("a/b"));
+    assert_eq!(v.clone().deref(), Path::new("a/b"));
+}
+
+#[test]
+fn dyn_clone () {
+    let v = ThinBox::<dyn Shape>::new_unsize(Square(2.0));
+    let w = v.clone();
+    drop(v);
+    assert_eq!(w.area(), 4.0);
+    assert_eq!(format!("{w:?}"), "Square(2.0)");
+}
+
+#[test]
+fn clone_panic () {
+    struct Bomb<'a>(usize, &'a Cell<usize>);
+
+    impl Clone for Bomb<'_> {
+        fn clone(&self) -> Self {
+            if self.0 == 2 {
+                panic!("boom")
+            }
+            Bomb(self.0, self.1)
+        }
+    }
+
+    impl Drop for Bomb<'_> {
+        fn drop(&mut self) {
+            self.1.set(self.1.get() + 1);
+        }
+    }
+
+    let drops = Cell::new(0);
+    let v = ThinBox::<[Bomb]>::from_fn(4, |i| Bomb(i, &drops));
+    assert!(catch_unwind(AssertUnwindSafe(|| v.clone())).is_err());
+    assert_eq!(drops.get(), 2);
+}
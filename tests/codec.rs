@@ -0,0 +1,83 @@
+#![cfg(any(feature = "borsh", feature = "bincode"))]
+
+use thinnbox::ThinBox;
+
+#[cfg(feature = "borsh")]
+#[test]
+fn borsh () {
+    let value = (ThinBox::new(7u32), ThinBox::<[u16]>::from_slice(&[1, 2, 3]), ThinBox::<str>::from("héllo"));
+    let bytes = borsh::to_vec(&value).unwrap();
+    assert_eq!(bytes, borsh::to_vec(&(7u32, vec![1u16, 2, 3], "héllo".to_string())).unwrap());
+
+    let (a, b, c) = borsh::from_slice::<(ThinBox<u32>, ThinBox<[u16]>, ThinBox<str>)>(&bytes).unwrap();
+    assert_eq!((*a, &*b, &*c), (7, &[1, 2, 3][..], "héllo"));
+
+    assert!(borsh::from_slice::<ThinBox<str>>(&[2, 0, 0, 0, 0xff, 0xfe]).is_err());
+    assert!(borsh::from_slice::<ThinBox<[u16]>>(&[3, 0, 0, 0, 1, 0]).is_err());
+}
+
+#[cfg(feature = "borsh")]
+#[test]
+fn borsh_untrusted_len () {
+    let err = borsh::from_slice::<ThinBox<[()]>>(&[0xff; 4]).unwrap_err();
+    assert_eq!(err.kind(), borsh::io::ErrorKind::InvalidData);
+
+    // the prefix claims far more than the input holds, so only a bounded block is allocated before running out
+    assert!(borsh::from_slice::<ThinBox<[u64]>>(&[0xff, 0xff, 0xff, 0xff, 1, 0]).is_err());
+    assert!(borsh::from_slice::<ThinBox<str>>(&[0xff, 0xff, 0xff, 0xff, b'a']).is_err());
+
+    // values longer than the preallocation limit are still read in full
+    let long = (0..300_000u64).collect::<Vec<_>>();
+    let b = borsh::from_slice::<ThinBox<[u64]>>(&borsh::to_vec(&long).unwrap()).unwrap();
+    assert_eq!(&*b, &long[..]);
+
+    let long = "ab".repeat(600_000);
+    let b = borsh::from_slice::<ThinBox<str>>(&borsh::to_vec(&long).unwrap()).unwrap();
+    assert_eq!(&*b, long);
+}
+
+#[cfg(feature = "bincode")]
+#[test]
+fn bincode () {
+    let config = bincode::config::standard();
+    let value = (ThinBox::new(7u32), ThinBox::<[u16]>::from_slice(&[1, 2, 3]), ThinBox::<str>::from("héllo"));
+    let bytes = bincode::encode_to_vec(&value, config).unwrap();
+    assert_eq!(bytes, bincode::encode_to_vec(&(7u32, vec![1u16, 2, 3], "héllo"), config).unwrap());
+
+    let ((a, b, c), read) = bincode::decode_from_slice::<(ThinBox<u32>, ThinBox<[u16]>, ThinBox<str>), _>(&bytes, config).unwrap();
+    assert_eq!((*a, &*b, &*c, read), (7, &[1, 2, 3][..], "héllo", bytes.len()));
+
+    let ((a, b, c), _) = bincode::borrow_decode_from_slice::<(ThinBox<u32>, ThinBox<[u16]>, ThinBox<str>), _>(&bytes, config).unwrap();
+    assert_eq!((*a, &*b, &*c), (7, &[1, 2, 3][..], "héllo"));
+
+    assert!(bincode::decode_from_slice::<ThinBox<str>, _>(&[2, 0xff, 0xfe], config).is_err());
+    assert!(bincode::decode_from_slice::<ThinBox<[u16]>, _>(&[3, 1], config).is_err());
+    assert!(bincode::decode_from_slice::<ThinBox<[u8]>, _>(&[100, 1], config.with_limit::<8>()).is_err());
+}
+
+#[cfg(feature = "bincode")]
+#[test]
+fn bincode_untrusted_len () {
+    let config = bincode::config::standard();
+    let huge = bincode::encode_to_vec(u64::MAX >> 1, config).unwrap();
+
+    // zero-sized elements round-trip like in a `Vec`, and count against the decoder's limit
+    let zst = bincode::encode_to_vec(ThinBox::<[()]>::from_slice(&[(); 3]), config).unwrap();
+    assert_eq!(zst, bincode::encode_to_vec(vec![(); 3], config).unwrap());
+    let (b, _) = bincode::decode_from_slice::<ThinBox<[()]>, _>(&zst, config).unwrap();
+    assert_eq!(b.len(), 3);
+
+    let err = bincode::decode_from_slice::<ThinBox<[()]>, _>(&huge, config.with_limit::<1024>()).unwrap_err();
+    assert!(matches!(err, bincode::error::DecodeError::LimitExceeded));
+
+    assert!(bincode::decode_from_slice::<ThinBox<[u64]>, _>(&[&huge[..], &[1]].concat(), config).is_err());
+    assert!(bincode::decode_from_slice::<ThinBox<str>, _>(&[&huge[..], b"a"].concat(), config).is_err());
+
+    let long = (0..300_000u64).collect::<Vec<_>>();
+    let (b, _) = bincode::decode_from_slice::<ThinBox<[u64]>, _>(&bincode::encode_to_vec(&long, config).unwrap(), config).unwrap();
+    assert_eq!(&*b, &long[..]);
+
+    let long = "ab".repeat(600_000);
+    let (b, _) = bincode::decode_from_slice::<ThinBox<str>, _>(&bincode::encode_to_vec(&long, config).unwrap(), config).unwrap();
+    assert_eq!(&*b, long);
+}
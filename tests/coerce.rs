@@ -0,0 +1,46 @@
+use std::{fmt::Debug, ops::Deref};
+use thinnbox::ThinBox;
+
+trait Super: Debug {
+    fn name(&self) -> &'static str;
+}
+
+trait Sub: Super {
+    fn id(&self) -> u32;
+}
+
+#[derive(Debug)]
+struct Foo(u32);
+
+impl Super for Foo {
+    fn name(&self) -> &'static str {
+        "foo"
+    }
+}
+
+impl Sub for Foo {
+    fn id(&self) -> u32 {
+        self.0
+    }
+}
+
+#[test]
+fn unsize () {
+    let v: ThinBox<dyn Sub> = ThinBox::new(Foo(3)).unsize();
+    assert_eq!(v.id(), 3);
+
+    let v: ThinBox<[u8]> = ThinBox::new([7u8; 16]).unsize();
+    assert_eq!(v.deref(), [7; 16]);
+    assert_eq!(v.metadata(), 16);
+}
+
+#[test]
+fn upcast () {
+    let v = ThinBox::<dyn Sub>::new_unsize(Foo(5));
+    let ptr = unsafe { v.as_raw() };
+
+    let v: ThinBox<dyn Super> = v.upcast();
+    assert_eq!(unsafe { v.as_raw() }, ptr);
+    assert_eq!(v.name(), "foo");
+    assert_eq!(format!("{v:?}"), "Foo(5)");
+}
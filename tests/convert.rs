@@ -0,0 +1,86 @@
+#![feature(allocator_api)]
+
+use std::{
+    alloc::{AllocError, Allocator, Global, Layout},
+    cell::Cell,
+    fmt::Debug,
+    ops::Deref,
+    ptr::NonNull,
+    rc::Rc,
+    sync::Arc,
+};
+use thinnbox::ThinBox;
+
+/// Counts allocations and deallocations. Resizing goes through the trait's default methods, so it counts as both.
+#[derive(Default)]
+struct Counting {
+    allocs: Cell<usize>,
+    frees: Cell<usize>,
+}
+
+unsafe impl Allocator for &Counting {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocs.set(self.allocs.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.frees.set(self.frees.get() + 1);
+        Global.deallocate(ptr, layout)
+    }
+}
+
+#[test]
+fn from_box () {
+    let boxed: Box<dyn Debug> = Box::new(vec![1, 2, 3]);
+    let v = ThinBox::from(boxed);
+    assert_eq!(format!("{v:?}"), "[1, 2, 3]");
+
+    let v = ThinBox::from(Box::<[u8]>::from([1u8, 2, 3]));
+    assert_eq!(v.deref(), [1, 2, 3]);
+
+    let v = ThinBox::<()>::from(Box::new(()));
+    assert_eq!(*v, ());
+}
+
+#[test]
+fn into_box () {
+    let v = ThinBox::<dyn Debug>::new_unsize(String::from("hello"));
+    assert_eq!(format!("{:?}", v.into_box()), "\"hello\"");
+
+    let v = ThinBox::<[()]>::from_fn(3, |_| ());
+    assert_eq!(v.into_box().len(), 3);
+}
+
+#[test]
+fn collections () {
+    let v = ThinBox::<[String]>::from(vec![String::from("a"), String::from("b")]);
+    assert_eq!(Vec::from(v), ["a", "b"]);
+
+    let v = ThinBox::<str>::from("hello");
+    assert_eq!(String::from(v), "hello");
+}
+
+#[test]
+fn from_vec_in () {
+    let alloc = Counting::default();
+    let mut v = Vec::with_capacity_in(8, &alloc);
+    v.extend((0..3).map(|x| x.to_string()));
+
+    // the elements are moved once, without shrinking the vector first
+    let v = ThinBox::<[String], _>::from(v);
+    assert_eq!(v.deref(), ["0", "1", "2"]);
+    assert_eq!((alloc.allocs.get(), alloc.frees.get()), (2, 1));
+
+    drop(v);
+    assert_eq!(alloc.frees.get(), 2);
+}
+
+#[test]
+fn shared () {
+    let rc = Rc::<[i32]>::from(ThinBox::from_slice(&[1, 2]));
+    assert_eq!(rc.deref(), [1, 2]);
+
+    let arc = Arc::<dyn Debug>::from(ThinBox::<dyn Debug>::new_unsize(5));
+    assert_eq!(format!("{arc:?}"), "5");
+}
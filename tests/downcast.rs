@@ -0,0 +1,39 @@
+use std::{any::Any, error::Error, fmt::Display};
+use thinnbox::ThinBox;
+
+#[derive(Debug, PartialEq)]
+struct MyError(u8);
+
+impl Display for MyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "error {}", self.0)
+    }
+}
+
+impl Error for MyError {}
+
+#[test]
+fn any () {
+    let v = ThinBox::<dyn Any + Send>::new_unsize(String::from("hello"));
+    assert!(v.is::<String>());
+    assert_eq!(v.downcast_ref::<String>().unwrap(), "hello");
+
+    let v = v.downcast::<i32>().unwrap_err();
+    let v = v.downcast::<String>().unwrap();
+    assert_eq!(*v, "hello");
+}
+
+#[test]
+fn any_mut () {
+    let mut v = ThinBox::<dyn Any>::new_unsize(1u128);
+    *v.downcast_mut::<u128>().unwrap() += 1;
+    assert_eq!(*v.downcast::<u128>().unwrap(), 2);
+}
+
+#[test]
+fn error () {
+    let v = ThinBox::<dyn Error + Send + Sync>::new_unsize(MyError(3));
+    assert_eq!(v.to_string(), "error 3");
+    assert_eq!(v.downcast_ref::<MyError>(), Some(&MyError(3)));
+    assert_eq!(*v.downcast::<MyError>().unwrap(), MyError(3));
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "derive")]
+
+use std::rc::Rc;
+use thinnbox::{ThinBox, ThinDst};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Span(u32, u32);
+
+#[derive(ThinDst)]
+#[repr(C)]
+struct Node {
+    kind: u8,
+    span: Span,
+    children: [u64],
+}
+
+#[derive(ThinDst)]
+#[repr(C)]
+struct Tuple<T>(String, [T]);
+
+#[test]
+fn build () {
+    let node = ThinBox::<Node>::build((3, Span(1, 9)), [10, 20, 30]);
+    assert_eq!(core::mem::size_of_val(&node), core::mem::size_of::<usize>());
+    assert_eq!(node.metadata(), 3);
+    assert_eq!((node.kind, node.span), (3, Span(1, 9)));
+    assert_eq!(&node.children, &[10, 20, 30]);
+    assert_eq!(core::mem::size_of_val(&*node), 8 + 8 + 3 * 8);
+}
+
+#[test]
+fn generic () {
+    let rc = Rc::new(());
+    let t = ThinBox::<Tuple<Rc<()>>>::build((String::from("a"),), (0..4).filter(|x| x % 2 == 0).map(|_| rc.clone()));
+    assert_eq!(t.0, "a");
+    assert_eq!(t.1.len(), 2);
+    assert_eq!(Rc::strong_count(&rc), 3);
+
+    drop(t);
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
@@ -0,0 +1,107 @@
+#![feature(allocator_api)]
+
+use std::{
+    alloc::{AllocError, Allocator, Global, Layout},
+    cell::Cell,
+    fmt::Debug,
+    num::ParseIntError,
+    mem::MaybeUninit,
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr::NonNull,
+};
+use thinnbox::ThinBox;
+
+/// Allocator that always fails.
+struct Failing;
+
+unsafe impl Allocator for Failing {
+    fn allocate(&self, _: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        Err(AllocError)
+    }
+
+    unsafe fn deallocate(&self, _: NonNull<u8>, _: Layout) {
+        unreachable!()
+    }
+}
+
+#[derive(Default)]
+struct Counting(Cell<usize>);
+
+unsafe impl Allocator for &Counting {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.set(self.0.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.0.set(self.0.get() - 1);
+        Global.deallocate(ptr, layout)
+    }
+}
+
+#[test]
+fn new_with () {
+    let v = ThinBox::new_with(|| [7u8; 16]);
+    assert_eq!(*v, [7; 16]);
+
+    let v = ThinBox::<dyn Debug>::new_unsize_with(|| [1, 2, 3]);
+    assert_eq!(format!("{v:?}"), "[1, 2, 3]");
+}
+
+#[test]
+fn try_new_with () {
+    let alloc = Counting::default();
+
+    let v = ThinBox::try_new_with_in(|| "5".parse::<i32>(), &alloc).unwrap();
+    assert_eq!(*v, 5);
+    assert_eq!(alloc.0.get(), 1);
+    drop(v);
+
+    let err: ParseIntError = ThinBox::try_new_with_in(|| "nope".parse::<i32>(), &alloc).unwrap_err();
+    assert_eq!(err, "nope".parse::<i32>().unwrap_err());
+    assert_eq!(alloc.0.get(), 0);
+
+    // allocation failures panic, like in `new_with`, and `f` isn't called
+    let res = catch_unwind(|| ThinBox::<i32, _>::try_new_with_in(|| -> Result<_, ParseIntError> { unreachable!() }, Failing));
+    assert!(res.is_err());
+}
+
+#[test]
+fn init_with_small_stack () {
+    const LEN: usize = 512 * 1024;
+
+    // the value is larger than the thread's whole stack, so it can only be built in place
+    let v = std::thread::Builder::new()
+        .stack_size(64 * 1024)
+        .spawn(|| unsafe {
+            ThinBox::<[u8; LEN]>::try_init_with(|slot: &mut MaybeUninit<[u8; LEN]>| {
+                let bytes = slot.as_mut_ptr().cast::<u8>();
+                for i in 0..LEN {
+                    bytes.add(i).write(i as u8);
+                }
+                Ok::<_, &str>(())
+            })
+            .map(|v| v.iter().enumerate().all(|(i, &x)| x == i as u8))
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+
+    assert_eq!(v, Ok(true));
+
+    let alloc = Counting::default();
+    let err = unsafe { ThinBox::<[u8; LEN], _>::try_init_with_in(|_| Err("nope"), &alloc) }.unwrap_err();
+    assert_eq!(err, "nope");
+    assert_eq!(alloc.0.get(), 0);
+}
+
+#[test]
+fn new_with_panic () {
+    let alloc = Counting::default();
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        ThinBox::<i32, _>::new_with_in(|| panic!("boom"), &alloc)
+    }));
+
+    assert!(res.is_err());
+    assert_eq!(alloc.0.get(), 0);
+}
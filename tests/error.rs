@@ -0,0 +1,38 @@
+#![feature(allocator_api)]
+
+use std::alloc::{AllocError, Allocator, Layout};
+use std::ptr::NonNull;
+use thinnbox::{ThinBox, ThinBoxError};
+
+struct Failing;
+
+unsafe impl Allocator for Failing {
+    fn allocate(&self, _: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        Err(AllocError)
+    }
+
+    unsafe fn deallocate(&self, _: NonNull<u8>, _: Layout) {
+        unreachable!()
+    }
+}
+
+#[test]
+fn alloc_error () {
+    let err = ThinBox::<[u64], _>::try_new_unsize_in([1u64, 2], Failing).unwrap_err();
+    assert_eq!(
+        err,
+        ThinBoxError::AllocError {
+            layout: Layout::from_size_align(24, 8).unwrap()
+        }
+    );
+    assert_eq!(err.to_string(), "memory allocation of 24 bytes with alignment 8 failed");
+}
+
+#[test]
+fn overflow () {
+    let err = ThinBox::<[u16]>::try_new_uninit_slice(usize::MAX).unwrap_err();
+    assert_eq!(err, ThinBoxError::CapacityOverflow);
+
+    let err = ThinBox::<[u8]>::try_new_uninit_slice(isize::MAX as usize).unwrap_err();
+    assert!(matches!(err, ThinBoxError::LayoutOverflow { metadata, .. } if metadata == Layout::new::<usize>()));
+}
@@ -0,0 +1,34 @@
+use std::{
+    ffi::{CStr, CString, OsStr, OsString},
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+use thinnbox::ThinBox;
+
+#[test]
+fn c_str () {
+    let v = ThinBox::<CStr>::from(c"hello");
+    assert_eq!(v.deref(), c"hello");
+    assert_eq!(unsafe { CStr::from_ptr(v.as_ptr()) }, c"hello");
+
+    let v: ThinBox<CStr> = CString::new("world").unwrap().into();
+    assert_eq!(CString::from(v).as_bytes(), b"world");
+}
+
+#[test]
+fn os_str () {
+    let v = ThinBox::<OsStr>::from(OsStr::new("hello"));
+    assert_eq!(v.deref(), "hello");
+
+    let v: ThinBox<OsStr> = OsString::from("world").into();
+    assert_eq!(OsString::from(v), "world");
+}
+
+#[test]
+fn path () {
+    let v = ThinBox::<Path>::from(Path::new("/usr/lib"));
+    assert_eq!(v.file_name().unwrap(), "lib");
+
+    let v: ThinBox<Path> = PathBuf::from("a/b").into();
+    assert_eq!(v.into_path_buf(), Path::new("a/b"));
+}
@@ -0,0 +1,30 @@
+use std::collections::{HashMap, HashSet};
+use thinnbox::{ByAddress, ThinBox};
+
+#[test]
+fn hash_by_value () {
+    let mut map = HashMap::new();
+    map.insert(ThinBox::<str>::from("foo"), 1);
+    map.insert(ThinBox::<str>::from("bar"), 2);
+    map.insert(ThinBox::<str>::from("foo"), 3);
+
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get("foo"), Some(&3));
+
+    let set: HashSet<ThinBox<[u8]>> = [ThinBox::from_slice(&[1, 2]), ThinBox::from_slice(&[1, 2])].into_iter().collect();
+    assert_eq!(set.len(), 1);
+    assert!(set.contains(&[1, 2][..]));
+}
+
+#[test]
+fn hash_by_address () {
+    let a = ByAddress(ThinBox::new(1));
+    let b = ByAddress(ThinBox::new(1));
+    assert_eq!(*a.0, *b.0);
+    assert_ne!(a, b);
+
+    let mut set = HashSet::new();
+    set.insert(a);
+    set.insert(b);
+    assert_eq!(set.len(), 2);
+}
@@ -0,0 +1,49 @@
+use std::rc::Rc;
+use thinnbox::{HeaderSlice, ThinBox};
+
+#[derive(Debug, PartialEq)]
+struct Packet {
+    kind: u8,
+    checksum: u32,
+}
+
+#[test]
+fn slice () {
+    let mut b = ThinBox::from_header_and_slice(Packet { kind: 1, checksum: 7 }, &[1u16, 2, 3]);
+    assert_eq!(core::mem::size_of_val(&b), core::mem::size_of::<usize>());
+    assert_eq!(b.metadata(), 3);
+    assert_eq!(b.header(), &Packet { kind: 1, checksum: 7 });
+    assert_eq!(b.slice(), &[1, 2, 3]);
+
+    b.header_mut().kind = 2;
+    b.slice_mut()[0] = 0;
+    assert_eq!(b.header().kind, 2);
+    assert_eq!(b.slice(), &[0, 2, 3]);
+}
+
+#[test]
+fn iter () {
+    let children = Rc::new(());
+    let b = ThinBox::<HeaderSlice<_, [_]>>::from_header_and_iter(
+        String::from("node"),
+        (0..5).filter(|x| x % 2 == 0).map(|_| children.clone()),
+    );
+
+    assert_eq!(b.header(), "node");
+    assert_eq!(b.slice().len(), 3);
+    assert_eq!(Rc::strong_count(&children), 4);
+    drop(b);
+    assert_eq!(Rc::strong_count(&children), 1);
+
+    let empty = ThinBox::<HeaderSlice<u64, [u8]>>::from_header_and_iter(9, []);
+    assert_eq!((*empty.header(), empty.slice()), (9, &[][..]));
+}
+
+#[test]
+fn str () {
+    let b = ThinBox::from_header_and_str(42u64, "payload");
+    assert_eq!(*b.header(), 42);
+    assert_eq!(b.slice(), "payload");
+    assert_eq!(b.metadata(), 7);
+    assert_eq!(b.heap_layout().size(), 8 + 8 + 7 + 1);
+}
@@ -0,0 +1,50 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+use thinnbox::ThinBox;
+
+fn poll_ready<F: Future + ?Sized>(mut f: Pin<&mut F>) -> F::Output {
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(x) = f.as_mut().poll(&mut cx) {
+            return x;
+        }
+    }
+}
+
+#[test]
+fn pin_async () {
+    let mut fut: Pin<ThinBox<dyn Future<Output = i32>>> = ThinBox::pin_unsize(async {
+        let x = async { 2 }.await;
+        x * 3
+    });
+
+    assert_eq!(poll_ready(fut.as_mut()), 6);
+}
+
+#[test]
+fn into_pin () {
+    let mut fut = Pin::from(ThinBox::new(async { "hello" }));
+    assert_eq!(poll_ready(fut.as_mut()), "hello");
+
+    let mut fut = ThinBox::pin(std::future::ready(1));
+    assert_eq!(poll_ready(Pin::new(&mut fut)), 1);
+}
+
+#[cfg(feature = "futures")]
+#[test]
+fn pin_stream () {
+    use futures::{stream, Stream, StreamExt};
+
+    let mut s: Pin<ThinBox<dyn Stream<Item = u32>>> = ThinBox::pin_unsize(stream::unfold(0, |x| async move {
+        (x < 3).then_some((x, x + 1))
+    }));
+
+    let mut items = Vec::new();
+    while let Some(x) = poll_ready(Pin::new(&mut s.next())) {
+        items.push(x);
+    }
+    assert_eq!(items, [0, 1, 2]);
+}
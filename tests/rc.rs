@@ -0,0 +1,146 @@
+use std::{
+    any::Any,
+    cell::Cell,
+    fmt::Display,
+    rc::Rc,
+    thread,
+};
+use thinnbox::{ThinArc, ThinArcWeak, ThinRc, ThinWeak};
+
+struct Dropper(Rc<Cell<usize>>);
+
+impl Drop for Dropper {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+#[test]
+fn one_word () {
+    assert_eq!(core::mem::size_of::<ThinRc<str>>(), core::mem::size_of::<usize>());
+    assert_eq!(core::mem::size_of::<ThinArc<dyn Display>>(), core::mem::size_of::<usize>());
+    assert_eq!(core::mem::size_of::<Option<ThinArc<[u8]>>>(), core::mem::size_of::<usize>());
+}
+
+#[test]
+fn rc () {
+    let a = ThinRc::<dyn Display>::new_unsize(7);
+    let b = a.clone();
+    assert!(ThinRc::ptr_eq(&a, &b));
+    assert_eq!(ThinRc::strong_count(&a), 2);
+    assert_eq!(a.to_string(), "7");
+
+    let raw = b.into_raw();
+    let b = unsafe { ThinRc::<dyn Display>::from_raw(raw) };
+    assert_eq!(b.to_string(), "7");
+    drop(a);
+    assert_eq!(ThinRc::strong_count(&b), 1);
+}
+
+#[test]
+fn rc_drop () {
+    let drops = Rc::new(Cell::new(0));
+    let a = ThinRc::new(Dropper(drops.clone()));
+    let b = a.clone();
+
+    drop(a);
+    assert_eq!(drops.get(), 0);
+    drop(b);
+    assert_eq!(drops.get(), 1);
+}
+
+#[test]
+fn rc_mut () {
+    let mut a = ThinRc::new(1);
+    *ThinRc::get_mut(&mut a).unwrap() += 1;
+
+    let b = a.clone();
+    assert!(ThinRc::get_mut(&mut a).is_none());
+
+    *ThinRc::make_mut(&mut a) += 1;
+    assert!(!ThinRc::ptr_eq(&a, &b));
+    assert_eq!((*a, *b), (3, 2));
+
+    let a = ThinRc::try_unwrap(a).unwrap();
+    let b = ThinRc::try_unwrap(b).unwrap();
+    assert_eq!((a, b), (3, 2));
+}
+
+#[test]
+fn rc_unsized_make_mut () {
+    let mut a = ThinRc::<[u8]>::new_unsize([1, 2, 3]);
+    let b = a.clone();
+    ThinRc::make_mut(&mut a)[0] = 0;
+
+    assert_eq!(&*a, &[0, 2, 3]);
+    assert_eq!(&*b, &[1, 2, 3]);
+    assert_eq!(a.metadata(), 3);
+}
+
+#[test]
+fn arc () {
+    let a = ThinArc::<[u8]>::new_unsize(*b"hello");
+    let handles = (0..4)
+        .map(|_| {
+            let a = a.clone();
+            thread::spawn(move || assert_eq!(&*a, b"hello"))
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(ThinArc::strong_count(&a), 1);
+}
+
+#[test]
+fn arc_mut () {
+    let mut a = ThinArc::new(String::from("a"));
+    ThinArc::get_mut(&mut a).unwrap().push('b');
+
+    let b = a.clone();
+    ThinArc::make_mut(&mut a).push('c');
+    assert_eq!((&**a, &**b), ("abc", "ab"));
+
+    let b = ThinArc::try_unwrap(b).unwrap();
+    assert_eq!(b, "ab");
+    assert_eq!(a.heap_layout().size(), 2 * core::mem::size_of::<usize>() + core::mem::size_of::<String>());
+}
+
+#[test]
+fn weak () {
+    let drops = Rc::new(Cell::new(0));
+    let a = ThinRc::<dyn Any>::new_unsize(Dropper(drops.clone()));
+    let w = ThinRc::downgrade(&a);
+    assert_eq!(core::mem::size_of_val(&w), core::mem::size_of::<usize>());
+    assert_eq!((w.strong_count(), w.weak_count()), (1, 1));
+
+    let b = w.upgrade().unwrap();
+    assert!(b.is::<Dropper>());
+    drop((a, b));
+    assert_eq!(drops.get(), 1);
+    assert!(w.upgrade().is_none());
+    assert_eq!((w.strong_count(), w.weak_count()), (0, 0));
+
+    let dangling = ThinWeak::<dyn Any>::new();
+    assert!(dangling.upgrade().is_none());
+    assert_eq!(dangling.clone().strong_count(), 0);
+}
+
+#[test]
+fn arc_weak () {
+    let a = ThinArc::<[u8]>::new_unsize(*b"shared");
+    let w = ThinArc::downgrade(&a);
+    assert_eq!(ThinArc::weak_count(&a), 1);
+
+    let mut a = a;
+    assert!(ThinArc::get_mut(&mut a).is_none());
+    let handle = thread::spawn(move || w.upgrade().map(|x| x.len()));
+    assert_eq!(handle.join().unwrap(), Some(6));
+    assert!(ThinArc::get_mut(&mut a).is_some());
+
+    let w = ThinArc::downgrade(&a);
+    drop(a);
+    assert!(w.upgrade().is_none());
+    assert!(ThinArcWeak::<[u8]>::default().upgrade().is_none());
+}
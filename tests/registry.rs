@@ -0,0 +1,86 @@
+#![cfg(feature = "registry")]
+
+use serde::{Deserialize, Serialize, Serializer};
+use std::sync::LazyLock;
+use thinnbox::{Registered, Registry, ThinBox, Typetag};
+
+trait Handler: Typetag {
+    fn handle(&self, input: u32) -> u32;
+}
+
+#[derive(Serialize, Deserialize)]
+struct Add(u32);
+
+#[derive(Serialize, Deserialize)]
+struct Mul {
+    by: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Unregistered;
+
+impl Handler for Add {
+    fn handle(&self, input: u32) -> u32 {
+        input + self.0
+    }
+}
+
+impl Handler for Mul {
+    fn handle(&self, input: u32) -> u32 {
+        input * self.by
+    }
+}
+
+impl Handler for Unregistered {
+    fn handle(&self, input: u32) -> u32 {
+        input
+    }
+}
+
+static HANDLERS: LazyLock<Registry<dyn Handler>> = LazyLock::new(|| {
+    let mut registry = Registry::new();
+    registry.register::<Add>("add").register::<Mul>("mul");
+    registry
+});
+
+impl Registered for dyn Handler {
+    fn registry() -> &'static Registry<Self> {
+        &HANDLERS
+    }
+}
+
+impl Serialize for dyn Handler {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        HANDLERS.serialize(self, serializer)
+    }
+}
+
+#[test]
+fn round_trip () {
+    let handlers: Vec<ThinBox<dyn Handler>> = vec![ThinBox::new_unsize(Add(2)), ThinBox::new_unsize(Mul { by: 3 })];
+    let json = serde_json::to_string(&handlers).unwrap();
+    assert_eq!(json, r#"[{"add":2},{"mul":{"by":3}}]"#);
+
+    let handlers = serde_json::from_str::<Vec<ThinBox<dyn Handler>>>(&json).unwrap();
+    assert_eq!(handlers.iter().fold(1, |acc, x| x.handle(acc)), 9);
+    assert_eq!(HANDLERS.tag_of(&*handlers[1]), Some("mul"));
+    assert_eq!(HANDLERS.tags().collect::<Vec<_>>(), ["add", "mul"]);
+}
+
+#[test]
+fn errors () {
+    let unregistered: ThinBox<dyn Handler> = ThinBox::new_unsize(Unregistered);
+    assert!(serde_json::to_string(&unregistered).is_err());
+
+    assert!(serde_json::from_str::<ThinBox<dyn Handler>>(r#"{"sub":1}"#).is_err());
+    assert!(serde_json::from_str::<ThinBox<dyn Handler>>(r#"{"add":"one"}"#).is_err());
+    assert!(serde_json::from_str::<ThinBox<dyn Handler>>(r#"{"add":1,"mul":{"by":2}}"#).is_err());
+    assert!(serde_json::from_str::<ThinBox<dyn Handler>>(r#"{}"#).is_err());
+}
+
+#[test]
+#[should_panic]
+fn duplicate_tag () {
+    let mut registry = Registry::<dyn Handler>::new();
+    registry.register::<Add>("op").register::<Mul>("op");
+}
@@ -0,0 +1,137 @@
+#![cfg(feature = "serde")]
+#![feature(allocator_api)]
+
+use serde::{Deserialize, Serialize};
+use std::{alloc::{AllocError, Allocator, Global, Layout}, cell::Cell, ffi::CStr, path::Path, ptr::NonNull};
+use thinnbox::{ThinBox, ThinBoxSeed, ThinString, ThinVec};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Entry {
+    key: ThinBox<str>,
+    values: ThinBox<[u32]>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Config {
+    name: ThinBox<str>,
+    path: ThinBox<Path>,
+    entries: ThinBox<[Entry]>,
+}
+
+#[test]
+fn round_trip () {
+    let config = Config {
+        name: ThinBox::from("test"),
+        path: ThinBox::from(Path::new("/tmp/config")),
+        entries: ThinBox::from_vec(vec![
+            Entry { key: ThinBox::from("a"), values: ThinBox::from_slice(&[1, 2]) },
+            Entry { key: ThinBox::from("b"), values: ThinBox::from_slice(&[]) },
+        ]),
+    };
+
+    let json = serde_json::to_string(&config).unwrap();
+    assert_eq!(json, r#"{"name":"test","path":"/tmp/config","entries":[{"key":"a","values":[1,2]},{"key":"b","values":[]}]}"#);
+    assert_eq!(serde_json::from_str::<Config>(&json).unwrap(), config);
+}
+
+#[test]
+fn c_str () {
+    let v: ThinBox<CStr> = serde_json::from_str("[104, 105]").unwrap();
+    assert_eq!(&*v, c"hi");
+    assert!(serde_json::from_str::<ThinBox<CStr>>("[104, 0, 105]").is_err());
+}
+
+#[test]
+fn size_hint () {
+    struct Hinted(Option<usize>, Vec<u16>);
+
+    impl<'de> serde::Deserializer<'de> for Hinted {
+        type Error = serde::de::value::Error;
+
+        fn deserialize_any<V: serde::de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            struct Seq(Option<usize>, std::vec::IntoIter<u16>);
+
+            impl<'de> serde::de::SeqAccess<'de> for Seq {
+                type Error = serde::de::value::Error;
+
+                fn next_element_seed<T: serde::de::DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+                    self.1.next().map(|x| seed.deserialize(serde::de::IntoDeserializer::into_deserializer(x))).transpose()
+                }
+
+                fn size_hint(&self) -> Option<usize> {
+                    self.0
+                }
+            }
+
+            visitor.visit_seq(Seq(self.0, self.1.into_iter()))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map struct enum identifier ignored_any
+        }
+    }
+
+    for hint in [None, Some(0), Some(2), Some(4), Some(8)] {
+        let v = ThinBox::<[u16]>::deserialize(Hinted(hint, vec![1, 2, 3, 4])).unwrap();
+        assert_eq!(&*v, &[1, 2, 3, 4]);
+        assert_eq!(v.heap_layout().size(), 8 + 4 * 2);
+    }
+}
+
+/// Allocator that counts its live blocks, and can't be created with `Default`.
+#[derive(Clone, Copy)]
+struct Counting<'a>(&'a Cell<usize>);
+
+unsafe impl Allocator for Counting<'_> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.set(self.0.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.0.set(self.0.get() - 1);
+        Global.deallocate(ptr, layout)
+    }
+}
+
+#[test]
+fn seed () {
+    use serde::de::DeserializeSeed;
+
+    let live = Cell::new(0);
+    let alloc = Counting(&live);
+    let mut de = serde_json::Deserializer::from_str(r#"[1, 2, 3] "hi" "/tmp" [104, 105] 7"#);
+
+    let slice = ThinBoxSeed::<[u8], _>::new(alloc).deserialize(&mut de).unwrap();
+    let str = ThinBoxSeed::<str, _>::new(alloc).deserialize(&mut de).unwrap();
+    let path = ThinBoxSeed::<Path, _>::new(alloc).deserialize(&mut de).unwrap();
+    let c_str = ThinBoxSeed::<CStr, _>::new(alloc).deserialize(&mut de).unwrap();
+    let sized = ThinBoxSeed::<u64, _>::new(alloc).deserialize(&mut de).unwrap();
+    de.end().unwrap();
+
+    assert_eq!((&*slice, &*str, &*path, &*c_str, *sized), (&[1, 2, 3][..], "hi", Path::new("/tmp"), c"hi", 7));
+    assert_eq!(live.get(), 5);
+
+    drop((slice, str, path, c_str, sized));
+    assert_eq!(live.get(), 0);
+}
+
+#[test]
+fn from_iter_in () {
+    let live = Cell::new(0);
+    let alloc = Counting(&live);
+
+    let sized = ThinBox::<Vec<u8>, _>::from_iter_in(0..3, alloc);
+    let slice = ThinBox::<[u8], _>::from_iter_in(0..3, alloc);
+    let str = ThinBox::<str, _>::from_iter_in(["a", "b"], alloc);
+    let vec = ThinVec::from_iter_in(0..3u8, alloc);
+    let string = ThinString::from_iter_in("ab".chars(), alloc);
+
+    assert_eq!((&**sized, &*slice, &*str, &*vec, &*string), (&[0, 1, 2][..], &[0, 1, 2][..], "ab", &[0, 1, 2][..], "ab"));
+    assert_eq!(live.get(), 5);
+
+    drop((sized, slice, str, vec, string));
+    assert_eq!(live.get(), 0);
+}
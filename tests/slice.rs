@@ -0,0 +1,100 @@
+#![feature(allocator_api)]
+
+use std::{
+    alloc::{AllocError, Allocator, Global, Layout},
+    cell::Cell,
+    ops::Deref,
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr::NonNull,
+};
+use thinnbox::ThinBox;
+
+struct DropCounter<'a>(&'a Cell<usize>);
+
+impl Drop for DropCounter<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+/// Allocator that can't shrink, and counts the live blocks.
+#[derive(Default)]
+struct NoShrink(Cell<usize>);
+
+unsafe impl Allocator for &NoShrink {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.set(self.0.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.0.set(self.0.get() - 1);
+        Global.deallocate(ptr, layout)
+    }
+
+    unsafe fn shrink(&self, _: NonNull<u8>, _: Layout, _: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        Err(AllocError)
+    }
+}
+
+/// Iterator whose exact size hint is larger than the number of items it yields.
+struct Short<'a>(usize, &'a Cell<usize>);
+
+impl<'a> Iterator for Short<'a> {
+    type Item = DropCounter<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0 = self.0.checked_sub(1)?;
+        Some(DropCounter(self.1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (4, Some(4))
+    }
+}
+
+#[test]
+fn from_iter () {
+    let v: ThinBox<[i32]> = (1..=4).collect();
+    assert_eq!(v.deref(), [1, 2, 3, 4]);
+
+    let v: ThinBox<[i32]> = (1..=10).filter(|x| x % 2 == 0).collect();
+    assert_eq!(v.deref(), [2, 4, 6, 8, 10]);
+}
+
+#[test]
+fn constructors () {
+    assert_eq!(ThinBox::from_slice(&[1, 2, 3]).deref(), [1, 2, 3]);
+    assert_eq!(ThinBox::from_vec(vec![String::from("a")]).deref(), ["a"]);
+    assert_eq!(ThinBox::from_fn(4, |i| i * 2).deref(), [0, 2, 4, 6]);
+    assert_eq!(ThinBox::repeat(7u8, 3).deref(), [7, 7, 7]);
+    assert_eq!(ThinBox::concat(&[&[1, 2], &[], &[3]]).deref(), [1, 2, 3]);
+    assert!(ThinBox::<[u8]>::from_vec(Vec::new()).is_empty());
+}
+
+#[test]
+fn from_fn_panic () {
+    let drops = Cell::new(0);
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        ThinBox::from_fn(5, |i| {
+            if i == 3 {
+                panic!("boom")
+            }
+            DropCounter(&drops)
+        })
+    }));
+
+    assert!(res.is_err());
+    assert_eq!(drops.get(), 3);
+}
+
+#[test]
+fn from_iter_shrink_fails () {
+    let alloc = NoShrink::default();
+    let drops = Cell::new(0);
+    let res = catch_unwind(AssertUnwindSafe(|| ThinBox::<[_], _>::from_iter_in(Short(2, &drops), &alloc)));
+
+    assert!(res.is_err());
+    assert_eq!(drops.get(), 2);
+    assert_eq!(alloc.0.get(), 0);
+}
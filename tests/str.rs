@@ -0,0 +1,45 @@
+use std::{collections::BTreeMap, ops::Deref};
+use thinnbox::ThinBox;
+
+#[test]
+fn from_str () {
+    let v = ThinBox::<str>::from("hello");
+    assert_eq!(v.deref(), "hello");
+    assert_eq!(v.to_string(), "hello");
+
+    let v: ThinBox<str> = String::from("world").into();
+    assert_eq!(v.len(), 5);
+
+    let v: ThinBox<str> = "parsed".parse().unwrap();
+    assert_eq!(v.deref(), "parsed");
+}
+
+#[test]
+fn utf8 () {
+    let bytes = ThinBox::from_slice("héllo".as_bytes());
+    let v = ThinBox::from_utf8(bytes).unwrap();
+    assert_eq!(v.deref(), "héllo");
+    assert_eq!(v.into_boxed_bytes().deref(), "héllo".as_bytes());
+
+    let err = ThinBox::from_utf8(ThinBox::from_slice(&[b'a', 0xff])).unwrap_err();
+    assert_eq!(err.utf8_error().valid_up_to(), 1);
+    assert_eq!(err.into_bytes().deref(), [b'a', 0xff]);
+}
+
+#[test]
+fn collect () {
+    let v: ThinBox<str> = ['a', 'b', 'ç'].into_iter().collect();
+    assert_eq!(v.deref(), "abç");
+    assert_eq!(v.heap_layout().size(), 8 + "abç".len());
+
+    let v: ThinBox<str> = ["foo", "", "bar"].into_iter().collect();
+    assert_eq!(v.deref(), "foobar");
+    assert_eq!(v.heap_layout().size(), 8 + 6);
+}
+
+#[test]
+fn map_key () {
+    let mut map = BTreeMap::new();
+    map.insert(ThinBox::<str>::from("key"), 1);
+    assert_eq!(map.get("key"), Some(&1));
+}
@@ -0,0 +1,86 @@
+#![feature(allocator_api)]
+
+use std::{
+    alloc::{AllocError, Allocator, Global, Layout},
+    cell::Cell,
+    fmt::Write,
+    ptr::NonNull,
+};
+use thinnbox::{ThinBox, ThinString};
+
+/// Allocator that counts the calls to `allocate`.
+#[derive(Default)]
+struct Counting(Cell<usize>);
+
+unsafe impl Allocator for &Counting {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.set(self.0.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.0.set(self.0.get() - 1);
+        Global.deallocate(ptr, layout)
+    }
+
+    unsafe fn shrink(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        Global.shrink(ptr, old, new)
+    }
+}
+
+#[test]
+fn empty () {
+    let s = ThinString::new();
+    assert_eq!(core::mem::size_of_val(&s), core::mem::size_of::<usize>());
+    assert_eq!((s.len(), s.capacity()), (0, 0));
+    assert_eq!(s, "");
+}
+
+#[test]
+fn edit () {
+    let mut s = ThinString::new();
+    s.push_str("héllo");
+    s.push('!');
+    s.insert_str(0, "¡");
+    s.insert(s.len() - 1, ' ');
+    assert_eq!(s, "¡héllo !");
+
+    assert_eq!(s.pop(), Some('!'));
+    s.truncate(s.len() - 1);
+    assert_eq!(&*s, "¡héllo");
+
+    write!(s, " {}", 42).unwrap();
+    assert_eq!(s.to_string(), "¡héllo 42");
+}
+
+#[test]
+#[should_panic]
+fn truncate_boundary () {
+    let mut s: ThinString = ThinString::from("é");
+    s.truncate(1);
+}
+
+#[test]
+fn into_thin_box () {
+    let mut s = ThinString::with_capacity(32);
+    s.extend(["foo", "bar"]);
+    let b: ThinBox<str> = s.into_thin_box();
+    assert_eq!(&*b, "foobar");
+    assert_eq!(b.metadata(), 6);
+
+    assert_eq!(&*ThinBox::from(ThinString::new()), "");
+}
+
+#[test]
+fn into_thin_box_in_place () {
+    // the block is shrunk rather than copied into a new one
+    let alloc = Counting::default();
+    let mut s = ThinString::with_capacity_in(32, &alloc);
+    s.push_str("hello");
+
+    let b = s.into_thin_box();
+    assert_eq!(&*b, "hello");
+    assert_eq!(alloc.0.get(), 1);
+    drop(b);
+    assert_eq!(alloc.0.get(), 0);
+}
@@ -0,0 +1,34 @@
+use std::fmt::Debug;
+use thinnbox::{TaggedThinBox, ThinBox};
+
+#[test]
+fn tag () {
+    let mut b = TaggedThinBox::<dyn Debug, 3>::new(ThinBox::new_unsize(String::from("hi")), 5);
+    assert_eq!(core::mem::size_of_val(&b), core::mem::size_of::<usize>());
+    assert_eq!(b.tag(), 5);
+    assert_eq!(format!("{:?}", &*b), "\"hi\"");
+
+    b.set_tag(7);
+    let b = b.with_tag(2);
+    assert_eq!(b.tag(), 2);
+
+    let (inner, tag) = b.into_inner();
+    assert_eq!((format!("{inner:?}"), tag), (String::from("\"hi\""), 2));
+}
+
+#[test]
+fn sized () {
+    let mut b = TaggedThinBox::<u64, 3>::new_sized(ThinBox::new(40), 1);
+    *b += 2;
+    assert_eq!((*b, b.tag()), (42, 1));
+
+    let c = b.clone().with_tag(6);
+    assert_eq!((*c, c.tag()), (42, 6));
+    assert_eq!(b.tag(), 1);
+}
+
+#[test]
+#[should_panic]
+fn overflowing_tag () {
+    TaggedThinBox::<[u8], 2>::new(ThinBox::from_slice(&[1, 2]), 4);
+}
@@ -0,0 +1,34 @@
+use std::ops::Deref;
+use thinnbox::ThinBox;
+
+#[test]
+fn uninit () {
+    let mut v = ThinBox::<[u64; 4]>::new_uninit();
+    v.write([1, 2, 3, 4]);
+    let v = unsafe { v.assume_init() };
+    assert_eq!(*v, [1, 2, 3, 4]);
+
+    let v = ThinBox::write(ThinBox::<String>::new_uninit(), String::from("hello"));
+    assert_eq!(v.as_str(), "hello");
+}
+
+#[test]
+fn zeroed () {
+    let v = unsafe { ThinBox::<[u32; 8]>::new_zeroed().assume_init() };
+    assert_eq!(*v, [0; 8]);
+
+    let v = unsafe { ThinBox::<[u16]>::new_zeroed_slice(5).assume_init() };
+    assert_eq!(v.deref(), [0; 5]);
+}
+
+#[test]
+fn uninit_slice () {
+    let mut v = ThinBox::<[usize]>::new_uninit_slice(3);
+    for (i, x) in v.iter_mut().enumerate() {
+        x.write(i);
+    }
+
+    let v = unsafe { v.assume_init() };
+    assert_eq!(v.deref(), [0, 1, 2]);
+    assert_eq!(v.metadata(), 3);
+}
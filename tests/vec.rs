@@ -0,0 +1,163 @@
+#![feature(allocator_api)]
+
+use std::{
+    alloc::{AllocError, Allocator, Global, Layout},
+    cell::Cell,
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr::NonNull,
+    rc::Rc,
+};
+use thinnbox::{ThinBox, ThinVec};
+
+/// Allocator that counts the calls to `allocate`, and can be made to fail when shrinking.
+#[derive(Default)]
+struct Counting {
+    allocs: Cell<usize>,
+    fail_shrink: bool,
+}
+
+unsafe impl Allocator for &Counting {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocs.set(self.allocs.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.allocs.set(self.allocs.get() - 1);
+        Global.deallocate(ptr, layout)
+    }
+
+    unsafe fn shrink(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        match self.fail_shrink {
+            true => Err(AllocError),
+            false => Global.shrink(ptr, old, new),
+        }
+    }
+}
+
+#[repr(align(32))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Aligned(u8);
+
+#[test]
+fn empty () {
+    let v = ThinVec::<u64>::new();
+    assert_eq!(core::mem::size_of_val(&v), core::mem::size_of::<usize>());
+    assert_eq!((v.len(), v.capacity()), (0, 0));
+    assert_eq!(&*v, &[] as &[u64]);
+
+    let b = v.into_thin_box();
+    assert_eq!(b.len(), 0);
+}
+
+#[test]
+fn push_pop () {
+    let mut v = ThinVec::new();
+    for i in 0..100 {
+        v.push(i);
+    }
+
+    assert_eq!(v.len(), 100);
+    assert!(v.capacity() >= 100);
+    assert_eq!(v.pop(), Some(99));
+    assert_eq!(v.iter().sum::<i32>(), (0..99).sum::<i32>());
+
+    v.shrink_to_fit();
+    assert_eq!(v.capacity(), 99);
+    v.clear();
+    v.shrink_to_fit();
+    assert_eq!(v.capacity(), 0);
+    assert_eq!(v.pop(), None);
+}
+
+#[test]
+fn insert_remove () {
+    let mut v: ThinVec<String> = ["a", "c"].into_iter().map(String::from).collect();
+    v.insert(1, "b".into());
+    v.insert(3, "d".into());
+    assert_eq!(v, ["a", "b", "c", "d"].into_iter().map(String::from).collect::<ThinVec<_>>());
+
+    assert_eq!(v.remove(0), "a");
+    assert_eq!(v.swap_remove(0), "b");
+    assert_eq!(&*v, &["d", "c"]);
+}
+
+#[test]
+fn drain () {
+    let mut v = ThinVec::<i32>::from(&[1, 2, 3, 4, 5][..]);
+    assert_eq!(v.drain(1..3).collect::<Vec<_>>(), [2, 3]);
+    assert_eq!(&*v, &[1, 4, 5]);
+
+    let mut drain = v.drain(..2);
+    assert_eq!(drain.next_back(), Some(4));
+    drop(drain);
+    assert_eq!(&*v, &[5]);
+
+    v.extend(&[6, 7]);
+    v.drain(..);
+    assert!(v.is_empty());
+}
+
+#[test]
+fn retain () {
+    let mut v = (0..10).collect::<ThinVec<_>>();
+    v.retain(|x| x % 3 == 0);
+    assert_eq!(&*v, &[0, 3, 6, 9]);
+
+    let drops = Rc::new(());
+    let mut v = (0..4).map(|i| (i, drops.clone())).collect::<ThinVec<_>>();
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        v.retain(|(i, _)| match i {
+            2 => panic!(),
+            i => i % 2 == 1,
+        })
+    }));
+
+    assert!(res.is_err());
+    assert_eq!(Rc::strong_count(&drops), 4);
+    assert_eq!(v.iter().map(|(i, _)| *i).collect::<Vec<_>>(), [1, 2, 3]);
+}
+
+#[test]
+fn into_thin_box () {
+    let mut v = ThinVec::with_capacity(10);
+    v.extend_from_slice(&[String::from("a"), String::from("b")]);
+    let b: ThinBox<[String]> = v.into_thin_box();
+    assert_eq!(&*b, &["a", "b"]);
+
+    let v = ThinVec::<Aligned>::from(&[Aligned(1), Aligned(2)][..]);
+    let b = ThinBox::<[Aligned]>::from(v);
+    assert_eq!(&*b, &[Aligned(1), Aligned(2)]);
+}
+
+#[test]
+fn into_thin_box_in_place () {
+    // the block is shrunk rather than copied into a new one
+    let alloc = Counting::default();
+    let mut v = ThinVec::<u8, _>::with_capacity_in(64, &alloc);
+    v.extend_from_slice(b"hello");
+    let b = v.into_thin_box();
+    assert_eq!((&*b, b.metadata()), (&b"hello"[..], 5));
+    assert_eq!(alloc.allocs.get(), 1);
+    drop(b);
+    assert_eq!(alloc.allocs.get(), 0);
+
+    let mut v = ThinVec::<u32, _>::with_capacity_in(3, &alloc);
+    v.extend_from_slice(&[1, 2, 3]);
+    let b = v.into_thin_box();
+    assert_eq!((&*b, b.metadata()), (&[1, 2, 3][..], 3));
+    assert_eq!(alloc.allocs.get(), 1);
+}
+
+#[test]
+fn into_thin_box_shrink_fails () {
+    let alloc = Counting { fail_shrink: true, ..Default::default() };
+    let item = Rc::new(());
+    let mut v = ThinVec::with_capacity_in(4, &alloc);
+    v.push(item.clone());
+
+    let res = catch_unwind(AssertUnwindSafe(|| v.into_thin_box()));
+    assert!(res.is_err());
+    assert_eq!(Rc::strong_count(&item), 1);
+    assert_eq!(alloc.allocs.get(), 0);
+}